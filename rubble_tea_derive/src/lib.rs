@@ -0,0 +1,81 @@
+use proc_macro::TokenStream;
+use quote::quote;
+use syn::{parse_macro_input, Data, DeriveInput, Fields};
+
+///Implements *rubble_tea::Event* for an enum. Exactly one variant must be
+///marked with `#[system]` and hold a single *SystemEvent*, system events are
+///wrapped in that variant and every other variant is a custom event.
+///```ignore
+///#[derive(Eq, PartialEq, Clone, Event)]
+///pub enum MyEvent {
+///    #[system]
+///    Se(SystemEvent),
+///    MyCustomEvent,
+///}
+///```
+#[proc_macro_derive(Event, attributes(system))]
+pub fn derive_event(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    match expand(&input) {
+        Ok(ts) => ts.into(),
+        Err(e) => e.to_compile_error().into(),
+    }
+}
+
+fn expand(input: &DeriveInput) -> syn::Result<proc_macro2::TokenStream> {
+    let data = match &input.data {
+        Data::Enum(data) => data,
+        _ => {
+            return Err(syn::Error::new_spanned(
+                &input.ident,
+                "Event can only be derived for enums",
+            ))
+        }
+    };
+    let mut system = data
+        .variants
+        .iter()
+        .filter(|v| v.attrs.iter().any(|a| a.path().is_ident("system")));
+    let variant = system.next().ok_or_else(|| {
+        syn::Error::new_spanned(
+            &input.ident,
+            "one variant must be marked with #[system] to derive Event",
+        )
+    })?;
+    if let Some(other) = system.next() {
+        return Err(syn::Error::new_spanned(
+            other,
+            "only one variant can be marked with #[system]",
+        ));
+    }
+    match &variant.fields {
+        Fields::Unnamed(f) if f.unnamed.len() == 1 => (),
+        _ => {
+            return Err(syn::Error::new_spanned(
+                variant,
+                "the #[system] variant must hold a single SystemEvent, e.g. Se(SystemEvent)",
+            ))
+        }
+    }
+    let name = &input.ident;
+    let var = &variant.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    let catch_all = if data.variants.len() > 1 {
+        quote!(_ => ::core::option::Option::None,)
+    } else {
+        quote!()
+    };
+    Ok(quote! {
+        impl #impl_generics ::rubble_tea::Event for #name #ty_generics #where_clause {
+            fn from_system_event(se: ::rubble_tea::SystemEvent) -> Self {
+                #name::#var(se)
+            }
+            fn to_system_event(&self) -> ::core::option::Option<::rubble_tea::SystemEvent> {
+                match self {
+                    #name::#var(se) => ::core::option::Option::Some(::core::clone::Clone::clone(se)),
+                    #catch_all
+                }
+            }
+        }
+    })
+}
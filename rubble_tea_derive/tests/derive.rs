@@ -0,0 +1,33 @@
+use rubble_tea::{Event as _, Key, SystemEvent};
+use rubble_tea_derive::Event;
+
+#[derive(Eq, PartialEq, Clone, Debug, Event)]
+enum MyEvent {
+    Tick,
+    #[system]
+    Se(SystemEvent),
+    Named {
+        id: u32,
+    },
+}
+
+#[derive(Eq, PartialEq, Clone, Debug, Event)]
+enum OnlySystem {
+    #[system]
+    Se(SystemEvent),
+}
+
+#[test]
+fn from_system_event() {
+    let se = SystemEvent::KeyPress(Key::Char('q'));
+    assert_eq!(MyEvent::from_system_event(se.clone()), MyEvent::Se(se));
+}
+
+#[test]
+fn to_system_event() {
+    let se = SystemEvent::WindowResize(80, 24);
+    assert_eq!(MyEvent::Se(se.clone()).to_system_event(), Some(se.clone()));
+    assert_eq!(MyEvent::Tick.to_system_event(), None);
+    assert_eq!(MyEvent::Named { id: 1 }.to_system_event(), None);
+    assert_eq!(OnlySystem::Se(se.clone()).to_system_event(), Some(se));
+}
@@ -0,0 +1,379 @@
+use crate::layout::Rect;
+use crate::style::{Color, Style, TextStyle, Token};
+use unicode_width::UnicodeWidthChar;
+
+///A single character on the screen and the styles it is drawn with. The cell
+///after a wide character has an empty symbol.
+#[derive(Eq, PartialEq, Clone, Debug)]
+pub struct Cell {
+    ///The grapheme drawn in the cell
+    pub symbol: String,
+    ///The styles of the cell, positions are ignored
+    pub styles: Vec<Style>,
+}
+
+impl Cell {
+    ///Creates a cell
+    pub fn new<S: Into<String>>(symbol: S, styles: &[Style]) -> Self {
+        Cell {
+            symbol: symbol.into(),
+            styles: styles.to_vec(),
+        }
+    }
+}
+
+impl Default for Cell {
+    fn default() -> Self {
+        Cell::new(" ", &[])
+    }
+}
+
+///A grid of styled cells that widgets paint into. Buffers are drawn by
+///returning them from *Model::rich_view* with *View::buffer*, the runtime
+///then only redraws the cells that changed since the last frame.
+#[derive(Eq, PartialEq, Clone, Debug, Default)]
+pub struct Buffer {
+    width: u16,
+    height: u16,
+    cells: Vec<Cell>,
+}
+
+//Sets a style, replacing the style of the same kind
+fn set_style(styles: &mut Vec<Style>, style: Style) {
+    styles.retain(|s| match (s, &style) {
+        (Style::TextColor(_), Style::TextColor(_)) => false,
+        (Style::BackgroundColor(_), Style::BackgroundColor(_)) => false,
+        (s, style) => s != style,
+    });
+    styles.push(style);
+}
+
+fn color(idx: u16) -> Option<Color> {
+    use Color::*;
+    [
+        Black,
+        Red,
+        Green,
+        Yellow,
+        Blue,
+        Magenta,
+        Cyan,
+        White,
+        LightBlack,
+        LightRed,
+        LightGreen,
+        LightYellow,
+        LightBlue,
+        LightMagenta,
+        LightCyan,
+        LightWhite,
+    ]
+    .get(idx as usize)
+    .cloned()
+}
+
+fn text_style(code: u16) -> Option<TextStyle> {
+    match code {
+        1 => Some(TextStyle::Bold),
+        2 => Some(TextStyle::Faint),
+        3 => Some(TextStyle::Italic),
+        4 => Some(TextStyle::Underline),
+        5 => Some(TextStyle::Blink),
+        7 => Some(TextStyle::Invert),
+        9 => Some(TextStyle::StrikeThrough),
+        _ => None,
+    }
+}
+
+//Applies the parameters of an SGR sequence to *styles*. Colors that can not
+//be expressed by *Color* are ignored.
+fn apply_sgr(styles: &mut Vec<Style>, params: &str) {
+    let codes: Vec<u16> = params.split(';').map(|c| c.parse().unwrap_or(0)).collect();
+    let mut i = 0;
+    while i < codes.len() {
+        match codes[i] {
+            0 => styles.clear(),
+            c @ (22..=29) => {
+                let off = text_style(c - 20);
+                styles.retain(|s| match (s, &off) {
+                    (Style::TextStyle(TextStyle::Bold | TextStyle::Faint), _) if c == 22 => false,
+                    (Style::TextStyle(s), Some(off)) => s != off,
+                    _ => true,
+                });
+            }
+            c @ (30..=37) => set_style(styles, Style::TextColor(color(c - 30).unwrap())),
+            c @ (90..=97) => set_style(styles, Style::TextColor(color(c - 82).unwrap())),
+            c @ (40..=47) => set_style(styles, Style::BackgroundColor(color(c - 40).unwrap())),
+            c @ (100..=107) => set_style(styles, Style::BackgroundColor(color(c - 92).unwrap())),
+            c @ (38 | 48) => {
+                if codes.get(i + 1) == Some(&5) {
+                    if let Some(color) = codes.get(i + 2).and_then(|n| color(*n)) {
+                        set_style(
+                            styles,
+                            if c == 38 {
+                                Style::TextColor(color)
+                            } else {
+                                Style::BackgroundColor(color)
+                            },
+                        );
+                    }
+                    i += 2;
+                } else if codes.get(i + 1) == Some(&2) {
+                    i += 4;
+                }
+            }
+            39 => styles.retain(|s| !matches!(s, Style::TextColor(_))),
+            49 => styles.retain(|s| !matches!(s, Style::BackgroundColor(_))),
+            c => {
+                if let Some(t) = text_style(c) {
+                    set_style(styles, Style::TextStyle(t));
+                }
+            }
+        }
+        i += 1;
+    }
+}
+
+impl Buffer {
+    ///Creates a buffer of blank cells
+    pub fn new(width: u16, height: u16) -> Self {
+        Buffer {
+            width,
+            height,
+            cells: vec![Cell::default(); width as usize * height as usize],
+        }
+    }
+    ///The area covered by the buffer
+    pub fn area(&self) -> Rect {
+        Rect::new(0, 0, self.width, self.height)
+    }
+    ///The cell at the given zero indexed position
+    pub fn get(&self, x: u16, y: u16) -> Option<&Cell> {
+        if x >= self.width || y >= self.height {
+            return None;
+        }
+        self.cells
+            .get(y as usize * self.width as usize + x as usize)
+    }
+    ///The cell at the given zero indexed position
+    pub fn get_mut(&mut self, x: u16, y: u16) -> Option<&mut Cell> {
+        if x >= self.width || y >= self.height {
+            return None;
+        }
+        self.cells
+            .get_mut(y as usize * self.width as usize + x as usize)
+    }
+    //The part of *area* that is inside the buffer
+    fn clip(&self, area: Rect) -> Rect {
+        let x = area.x.min(self.width);
+        let y = area.y.min(self.height);
+        Rect::new(
+            x,
+            y,
+            area.width.min(self.width - x),
+            area.height.min(self.height - y),
+        )
+    }
+    ///Sets every cell in *area* to *cell*
+    pub fn fill(&mut self, area: Rect, cell: &Cell) {
+        let area = self.clip(area);
+        for y in area.y..area.y + area.height {
+            self.split_wide(y, area.x, area.x + area.width);
+            for x in area.x..area.x + area.width {
+                *self.get_mut(x, y).unwrap() = cell.clone();
+            }
+        }
+    }
+    //Wide characters that are cut by the edges of the columns from *left* to
+    //*right* are about to lose one of their halves, the half outside of the
+    //columns is turned into a space
+    fn split_wide(&mut self, y: u16, left: u16, right: u16) {
+        let cut_at_left = self.get(left, y).is_some_and(|c| c.symbol.is_empty());
+        if let Some(cell) = left.checked_sub(1).and_then(|x| self.get_mut(x, y)) {
+            if cut_at_left {
+                cell.symbol = String::from(" ");
+            }
+        }
+        if let Some(cell) = self.get_mut(right, y) {
+            if cell.symbol.is_empty() {
+                cell.symbol = String::from(" ");
+            }
+        }
+    }
+    ///Draws a box around the edges of *area*
+    pub fn border(&mut self, area: Rect, styles: &[Style]) {
+        if area.width < 2 || area.height < 2 {
+            return;
+        }
+        let (right, bottom) = (area.x + area.width - 1, area.y + area.height - 1);
+        let mut set = |x: u16, y: u16, s: &str| {
+            if let Some(c) = self.get_mut(x, y) {
+                *c = Cell::new(s, styles);
+            }
+        };
+        for x in area.x + 1..right {
+            set(x, area.y, "─");
+            set(x, bottom, "─");
+        }
+        for y in area.y + 1..bottom {
+            set(area.x, y, "│");
+            set(right, y, "│");
+        }
+        set(area.x, area.y, "┌");
+        set(right, area.y, "┐");
+        set(area.x, bottom, "└");
+        set(right, bottom, "┘");
+    }
+    //Puts a character at (x, y) and returns the column after it, characters
+    //that do not fit before *right* are dropped
+    fn put(&mut self, x: u16, y: u16, c: char, styles: &[Style], right: u16) -> u16 {
+        let w = c.width().unwrap_or(0) as u16;
+        if w == 0 {
+            if let Some(cell) = x.checked_sub(1).and_then(|x| self.get_mut(x, y)) {
+                cell.symbol.push(c);
+            }
+            return x;
+        }
+        if x + w > right {
+            return right;
+        }
+        self.split_wide(y, x, x + w);
+        *self.get_mut(x, y).unwrap() = Cell::new(c, styles);
+        for i in 1..w {
+            *self.get_mut(x + i, y).unwrap() = Cell::new("", styles);
+        }
+        x + w
+    }
+    ///Writes *s* starting at the given position with the given styles, the
+    ///text is cut at the right edge of the buffer. Returns the column after
+    ///the last character.
+    pub fn set_string(&mut self, x: u16, y: u16, s: &str, styles: &[Style]) -> u16 {
+        let mut x = x;
+        if y >= self.height {
+            return x;
+        }
+        for c in s.chars().filter(|c| !c.is_control()) {
+            x = self.put(x, y, c, styles, self.width);
+        }
+        x
+    }
+    ///Writes text containing escape sequences into *area*, this is how
+    ///widgets paint their views. Lines start at the left of the area, colors
+    ///and text styles are turned into the styles of the cells and cursor
+    ///movements are followed. Other escape sequences are dropped and text
+    ///outside of the area is cut.
+    pub fn set_styled(&mut self, area: Rect, s: &str) {
+        let area = self.clip(area);
+        let (right, bottom) = (area.x + area.width, area.y + area.height);
+        let (mut x, mut y) = (area.x, area.y);
+        let mut styles = Vec::new();
+        for t in crate::style::tokens(s) {
+            match t {
+                Token::Escape(e) if e.starts_with("\x1b[") && e.ends_with('m') => {
+                    apply_sgr(&mut styles, &e[2..e.len() - 1])
+                }
+                Token::Escape(e) if e.starts_with("\x1b[") && e.ends_with('H') => {
+                    let mut pos = e[2..e.len() - 1].split(';').map(|n| n.parse().unwrap_or(1));
+                    y = pos.next().unwrap_or(1).max(1) - 1;
+                    x = pos.next().unwrap_or(1).max(1) - 1;
+                }
+                Token::Escape(_) => (),
+                Token::Char('\n') => {
+                    x = area.x;
+                    y += 1;
+                }
+                Token::Char(c) if c.is_control() => (),
+                Token::Char(c) => {
+                    if (area.x..right).contains(&x) && (area.y..bottom).contains(&y) {
+                        x = self.put(x, y, c, &styles, right);
+                    } else {
+                        x += c.width().unwrap_or(0) as u16;
+                    }
+                }
+            }
+        }
+    }
+    ///Returns the escape sequences that turn the screen showing *prev* into
+    ///this buffer, or that draw the whole buffer when there is no *prev*.
+    ///The cursor is only moved when the changed cells are not next to each
+    ///other and styles are only written when they change.
+    pub fn diff(&self, prev: Option<&Buffer>) -> String {
+        let mut ret = String::new();
+        let mut pos = None;
+        let mut styles: &[Style] = &[];
+        for y in 0..self.height {
+            for x in 0..self.width {
+                let cell = self.get(x, y).unwrap();
+                if cell.symbol.is_empty() || prev.and_then(|p| p.get(x, y)) == Some(cell) {
+                    continue;
+                }
+                if pos != Some((x, y)) {
+                    ret += &termion::cursor::Goto(x + 1, y + 1).to_string();
+                }
+                if cell.styles != styles {
+                    if !styles.is_empty() {
+                        ret += termion::style::Reset.as_ref();
+                    }
+                    for s in &cell.styles {
+                        if !matches!(s, Style::Position(_)) {
+                            ret += &s.to_string();
+                        }
+                    }
+                    styles = &cell.styles;
+                }
+                ret += &cell.symbol;
+                pos = Some((x + crate::style::width(&cell.symbol) as u16, y));
+            }
+        }
+        if !styles.is_empty() {
+            ret += termion::style::Reset.as_ref();
+        }
+        ret
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::style::StyleSheet;
+
+    #[test]
+    fn styled_text() {
+        let mut buf = Buffer::new(4, 2);
+        let red = StyleSheet::new()
+            .add(Style::TextColor(Color::Red))
+            .render("ab");
+        buf.set_styled(Rect::new(1, 0, 3, 2), &format!("{}c\r\n日本", red));
+        assert_eq!(
+            buf.get(1, 0),
+            Some(&Cell::new("a", &[Style::TextColor(Color::Red)]))
+        );
+        assert_eq!(buf.get(3, 0), Some(&Cell::new("c", &[])));
+        assert_eq!(buf.get(1, 1), Some(&Cell::new("日", &[])));
+        assert_eq!(buf.get(2, 1), Some(&Cell::new("", &[])));
+        assert_eq!(buf.get(3, 1), Some(&Cell::default()));
+    }
+    #[test]
+    fn diff_only_changed_cells() {
+        let mut prev = Buffer::new(5, 1);
+        prev.set_string(0, 0, "hello", &[]);
+        assert_eq!(prev.diff(None), "\x1b[1;1Hhello");
+        let mut buf = prev.clone();
+        buf.set_string(1, 0, "EL", &[Style::TextStyle(TextStyle::Bold)]);
+        buf.set_string(4, 0, "!", &[]);
+        assert_eq!(buf.diff(Some(&prev)), "\x1b[1;2H\x1b[1mEL\x1b[1;5H\x1b[m!");
+    }
+    #[test]
+    fn border() {
+        let mut buf = Buffer::new(3, 3);
+        buf.border(buf.area(), &[]);
+        let rows: Vec<String> = (0..3)
+            .map(|y| {
+                (0..3)
+                    .map(|x| buf.get(x, y).unwrap().symbol.clone())
+                    .collect()
+            })
+            .collect();
+        assert_eq!(rows, vec!["┌─┐", "│ │", "└─┘"]);
+    }
+}
@@ -0,0 +1,108 @@
+use std::io::Write;
+use std::time::Instant;
+
+//Escapes *s* so that it can be put inside of a JSON string
+fn json_escape(s: &str) -> String {
+    let mut ret = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '"' => ret += "\\\"",
+            '\\' => ret += "\\\\",
+            '\n' => ret += "\\n",
+            '\r' => ret += "\\r",
+            '\t' => ret += "\\t",
+            c if (c as u32) < 0x20 || c == '\u{7f}' => ret += &format!("\\u{:04x}", c as u32),
+            c => ret.push(c),
+        }
+    }
+    ret
+}
+
+///Writes the frames emmited by the runtime to an asciicast v2 file
+pub(crate) struct CastWriter<W: Write> {
+    out: W,
+    start: Instant,
+}
+
+impl CastWriter<std::io::BufWriter<std::fs::File>> {
+    pub(crate) fn create<P: AsRef<std::path::Path>>(
+        path: P,
+        width: u16,
+        height: u16,
+    ) -> std::io::Result<Self> {
+        CastWriter::new(
+            std::io::BufWriter::new(std::fs::File::create(path)?),
+            width,
+            height,
+        )
+    }
+}
+
+impl<W: Write> CastWriter<W> {
+    pub(crate) fn new(mut out: W, width: u16, height: u16) -> std::io::Result<Self> {
+        let timestamp = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+        let term = std::env::var("TERM").unwrap_or_default();
+        writeln!(
+            out,
+            "{{\"version\": 2, \"width\": {}, \"height\": {}, \"timestamp\": {}, \"env\": {{\"TERM\": \"{}\"}}}}",
+            width,
+            height,
+            timestamp,
+            json_escape(&term)
+        )?;
+        Ok(CastWriter {
+            out,
+            start: Instant::now(),
+        })
+    }
+    fn event(&mut self, code: &str, data: &str) {
+        //A frame that can not be written is lost, the program itself carries on
+        let _ = writeln!(
+            self.out,
+            "[{:.6}, \"{}\", \"{}\"]",
+            self.start.elapsed().as_secs_f64(),
+            code,
+            json_escape(data)
+        )
+        .and_then(|_| self.out.flush());
+    }
+    ///Records the bytes written to the terminal
+    pub(crate) fn output(&mut self, data: &str) {
+        self.event("o", data)
+    }
+    ///Records that the terminal has been resized
+    pub(crate) fn resize(&mut self, width: u16, height: u16) {
+        self.event("r", &format!("{}x{}", width, height))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn escape() {
+        assert_eq!(
+            json_escape("a\"b\\c\n\r\u{1b}[1m"),
+            "a\\\"b\\\\c\\n\\r\\u001b[1m"
+        );
+    }
+    #[test]
+    fn cast_file() {
+        let mut out = Vec::new();
+        {
+            let mut cast = CastWriter::new(&mut out, 80, 24).unwrap();
+            cast.output("hi\r\n");
+            cast.resize(100, 30);
+        }
+        let out = String::from_utf8(out).unwrap();
+        let lines: Vec<&str> = out.lines().collect();
+        assert_eq!(lines.len(), 3);
+        assert!(lines[0].starts_with("{\"version\": 2, \"width\": 80, \"height\": 24,"));
+        assert!(lines[1].starts_with('[') && lines[1].ends_with(", \"o\", \"hi\\r\\n\"]"));
+        assert!(lines[2].ends_with(", \"r\", \"100x30\"]"));
+    }
+}
@@ -0,0 +1,254 @@
+use crate::{Selection, SystemEvent};
+use std::collections::VecDeque;
+use std::io::Read;
+
+const BASE64_TABLE: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+pub(crate) fn base64_encode(data: &[u8]) -> String {
+    let mut ret = String::with_capacity(data.len().div_ceil(3) * 4);
+    for chunk in data.chunks(3) {
+        let b = [
+            chunk[0],
+            *chunk.get(1).unwrap_or(&0),
+            *chunk.get(2).unwrap_or(&0),
+        ];
+        let n = (b[0] as u32) << 16 | (b[1] as u32) << 8 | b[2] as u32;
+        for i in 0..4 {
+            if i <= chunk.len() {
+                ret.push(BASE64_TABLE[(n >> (18 - i * 6)) as usize & 0x3f] as char);
+            } else {
+                ret.push('=');
+            }
+        }
+    }
+    ret
+}
+
+pub(crate) fn base64_decode(s: &str) -> Option<Vec<u8>> {
+    let mut ret = Vec::with_capacity(s.len() / 4 * 3);
+    let mut acc: u32 = 0;
+    let mut bits = 0;
+    for c in s.bytes().filter(|c| *c != b'=') {
+        let v = BASE64_TABLE.iter().position(|x| *x == c)? as u32;
+        acc = acc << 6 | v;
+        bits += 6;
+        if bits >= 8 {
+            bits -= 8;
+            ret.push((acc >> bits) as u8);
+            acc &= (1 << bits) - 1;
+        }
+    }
+    Some(ret)
+}
+
+fn selection_code(s: &Selection) -> char {
+    match s {
+        Selection::Clipboard => 'c',
+        Selection::Primary => 'p',
+    }
+}
+
+///Returns the OSC 52 sequence that sets the given selection to *text*
+pub(crate) fn set_sequence(s: &Selection, text: &str) -> String {
+    format!(
+        "\x1b]52;{};{}\x07",
+        selection_code(s),
+        base64_encode(text.as_bytes())
+    )
+}
+
+///Returns the OSC 52 sequence that asks the terminal for the given selection
+pub(crate) fn request_sequence(s: &Selection) -> String {
+    format!("\x1b]52;{};?\x07", selection_code(s))
+}
+
+//Parses the body of an OSC sequence (without the leading `ESC ]` and the terminator)
+fn parse_reply(body: &[u8]) -> Option<SystemEvent> {
+    let body = std::str::from_utf8(body).ok()?;
+    let mut parts = body.splitn(3, ';');
+    if parts.next()? != "52" {
+        return None;
+    }
+    let selection = match parts.next()?.chars().next() {
+        Some('p') => Selection::Primary,
+        _ => Selection::Clipboard,
+    };
+    let data = base64_decode(parts.next()?)?;
+    Some(SystemEvent::Clipboard(
+        selection,
+        String::from_utf8_lossy(&data).into_owned(),
+    ))
+}
+
+enum OscState {
+    Normal,
+    Escape,
+    Body,
+    BodyEscape,
+}
+
+//Only OSC 52 replies are taken out of the input, anything else that starts
+//with `ESC ]` (such as alt+]) is passed on as key presses
+const REPLY_PREFIX: &[u8] = b"52;";
+//Replies longer than this are not clipboard replies we asked for
+const MAX_REPLY_LEN: usize = 1 << 20;
+
+///A reader that removes OSC 52 sequences from the terminal's input and turns
+///them into clipboard events. termion does not understand OSC sequences so
+///without this the replies would show up as a stream of key presses.
+pub(crate) struct OscFilter<R: Read, F: FnMut(SystemEvent)> {
+    inner: R,
+    on_reply: F,
+    state: OscState,
+    body: Vec<u8>,
+    pending: VecDeque<u8>,
+}
+
+impl<R: Read, F: FnMut(SystemEvent)> OscFilter<R, F> {
+    pub(crate) fn new(inner: R, on_reply: F) -> Self {
+        OscFilter {
+            inner,
+            on_reply,
+            state: OscState::Normal,
+            body: Vec::new(),
+            pending: VecDeque::new(),
+        }
+    }
+    fn finish_body(&mut self) {
+        if let Some(e) = parse_reply(&self.body) {
+            (self.on_reply)(e);
+        }
+        self.body.clear();
+        self.state = OscState::Normal;
+    }
+    //Gives the bytes of something that turned out not to be a reply back as
+    //key presses
+    fn abort_body(&mut self) {
+        self.pending.extend([0x1b, b']']);
+        self.pending.extend(self.body.drain(..));
+        self.state = OscState::Normal;
+    }
+    fn filter(&mut self, chunk: &[u8]) {
+        for &b in chunk {
+            match self.state {
+                OscState::Normal if b == 0x1b => self.state = OscState::Escape,
+                OscState::Normal => self.pending.push_back(b),
+                OscState::Escape if b == b']' => self.state = OscState::Body,
+                OscState::Escape => {
+                    self.pending.push_back(0x1b);
+                    self.pending.push_back(b);
+                    self.state = OscState::Normal;
+                }
+                OscState::Body if self.body.len() < REPLY_PREFIX.len() => {
+                    self.body.push(b);
+                    if !REPLY_PREFIX.starts_with(&self.body) {
+                        self.abort_body();
+                    }
+                }
+                OscState::Body if b == 0x07 => self.finish_body(),
+                OscState::Body if b == 0x1b => self.state = OscState::BodyEscape,
+                OscState::Body if self.body.len() >= MAX_REPLY_LEN => {
+                    self.body.push(b);
+                    self.abort_body();
+                }
+                OscState::Body => self.body.push(b),
+                OscState::BodyEscape if b == b'\\' => self.finish_body(),
+                OscState::BodyEscape => {
+                    self.body.extend([0x1b, b]);
+                    self.abort_body();
+                }
+            }
+        }
+        //A lone escape at the end of a chunk is a key press, leaving it in the
+        //state machine would make termion miss it. The same goes for the start
+        //of an OSC sequence that could still be alt+]
+        match self.state {
+            OscState::Escape => {
+                self.pending.push_back(0x1b);
+                self.state = OscState::Normal;
+            }
+            OscState::Body if self.body.len() < REPLY_PREFIX.len() => self.abort_body(),
+            _ => (),
+        }
+    }
+}
+
+impl<R: Read, F: FnMut(SystemEvent)> Read for OscFilter<R, F> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let mut chunk = [0u8; 1024];
+        while self.pending.is_empty() {
+            let n = self.inner.read(&mut chunk)?;
+            if n == 0 {
+                return Ok(0);
+            }
+            self.filter(&chunk[..n]);
+        }
+        let n = buf.len().min(self.pending.len());
+        for (i, b) in self.pending.drain(..n).enumerate() {
+            buf[i] = b;
+        }
+        Ok(n)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn base64_round_trip() {
+        for s in ["", "a", "ab", "abc", "hello world", "ünïcödé"] {
+            let encoded = base64_encode(s.as_bytes());
+            assert_eq!(base64_decode(&encoded).unwrap(), s.as_bytes());
+        }
+        assert_eq!(base64_encode(b"hello"), "aGVsbG8=");
+    }
+    #[test]
+    fn set_clipboard_sequence() {
+        assert_eq!(
+            set_sequence(&Selection::Clipboard, "hello"),
+            "\x1b]52;c;aGVsbG8=\x07"
+        );
+        assert_eq!(
+            set_sequence(&Selection::Primary, "hello"),
+            "\x1b]52;p;aGVsbG8=\x07"
+        );
+    }
+    #[test]
+    fn filter_clipboard_reply() {
+        let input: &[u8] = b"a\x1b]52;c;aGVsbG8=\x07b\x1b]52;p;aGk=\x1b\\c";
+        let mut replies = Vec::new();
+        let mut out = String::new();
+        OscFilter::new(input, |e| replies.push(e))
+            .read_to_string(&mut out)
+            .unwrap();
+        assert_eq!(out, "abc");
+        assert!(
+            replies
+                == vec![
+                    SystemEvent::Clipboard(Selection::Clipboard, "hello".to_string()),
+                    SystemEvent::Clipboard(Selection::Primary, "hi".to_string()),
+                ]
+        );
+    }
+    #[test]
+    fn filter_keeps_alt_bracket() {
+        let mut out = Vec::new();
+        OscFilter::new(&b"\x1b]abc"[..], |_| ())
+            .read_to_end(&mut out)
+            .unwrap();
+        assert_eq!(out, b"\x1b]abc");
+        let mut out = Vec::new();
+        OscFilter::new(&b"\x1b]52;c;aGk=\x1bxy"[..], |_| ())
+            .read_to_end(&mut out)
+            .unwrap();
+        assert_eq!(out, b"\x1b]52;c;aGk=\x1bxy");
+    }
+    #[test]
+    fn filter_keeps_escape_sequences() {
+        let input: &[u8] = b"\x1b[A\x1b";
+        let mut out = Vec::new();
+        OscFilter::new(input, |_| ()).read_to_end(&mut out).unwrap();
+        assert_eq!(out, b"\x1b[A\x1b");
+    }
+}
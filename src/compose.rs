@@ -0,0 +1,159 @@
+use crate::widgets::Widget;
+use crate::{Event, Model};
+use std::sync::Arc;
+
+type Wrap<C, P> = Arc<dyn Fn(C) -> P + Send + Sync>;
+type Unwrap<C, P> = Box<dyn Fn(&P) -> Option<C>>;
+
+///Turns commands that return child events into commands that return parent
+///events using *wrap*
+pub fn map_cmds<C: Event, P: Event, F: Fn(C) -> P + Send + Sync + 'static>(
+    cmds: Vec<Box<dyn FnOnce() -> C + Send + 'static>>,
+    wrap: F,
+) -> Vec<Box<dyn FnOnce() -> P + Send + 'static>> {
+    let wrap: Wrap<C, P> = Arc::new(wrap);
+    map_cmds_arc(cmds, &wrap)
+}
+
+fn map_cmds_arc<C: Event, P: Event>(
+    cmds: Vec<Box<dyn FnOnce() -> C + Send + 'static>>,
+    wrap: &Wrap<C, P>,
+) -> Vec<Box<dyn FnOnce() -> P + Send + 'static>> {
+    cmds.into_iter()
+        .map(|c| {
+            let wrap = wrap.clone();
+            Box::new(move || wrap(c())) as Box<dyn FnOnce() -> P + Send + 'static>
+        })
+        .collect()
+}
+
+///A model or widget whose events are of type *C* embedded in a parent whose
+///events are of type *P*. The child only sees the parent events that *unwrap*
+///turns into child events, and the commands it returns are mapped back into
+///parent events with *wrap*.
+pub struct Child<C: Event, P: Event, M> {
+    inner: M,
+    wrap: Wrap<C, P>,
+    unwrap: Unwrap<C, P>,
+}
+
+impl<C: Event, P: Event, M> Child<C, P, M> {
+    ///Creates a new child with
+    ///*inner* being the embedded model or widget
+    ///*wrap* turning child events into parent events
+    ///*unwrap* turning the parent events meant for the child into child events
+    pub fn new<W, U>(inner: M, wrap: W, unwrap: U) -> Self
+    where
+        W: Fn(C) -> P + Send + Sync + 'static,
+        U: Fn(&P) -> Option<C> + 'static,
+    {
+        Child {
+            inner,
+            wrap: Arc::new(wrap),
+            unwrap: Box::new(unwrap),
+        }
+    }
+    ///The embedded model or widget
+    pub fn inner(&self) -> &M {
+        &self.inner
+    }
+    ///The embedded model or widget
+    pub fn inner_mut(&mut self) -> &mut M {
+        &mut self.inner
+    }
+}
+
+impl<C: Event, P: Event, M: Model<C>> Model<P> for Child<C, P, M> {
+    fn update(&mut self, e: &P) -> Vec<Box<dyn FnOnce() -> P + Send + 'static>> {
+        match (self.unwrap)(e) {
+            Some(c) => map_cmds_arc(self.inner.update(&c), &self.wrap),
+            None => vec![],
+        }
+    }
+    fn view(&self) -> String {
+        self.inner.view()
+    }
+    fn rich_view(&self) -> crate::view::View {
+        self.inner.rich_view()
+    }
+}
+
+impl<C: Event, P: Event, M: Widget<C>> Widget<P> for Child<C, P, M> {
+    fn init(&mut self) -> Vec<Box<dyn FnOnce() -> P + Send + 'static>> {
+        map_cmds_arc(self.inner.init(), &self.wrap)
+    }
+    fn update(&mut self, e: &P) -> Vec<Box<dyn FnOnce() -> P + Send + 'static>> {
+        match (self.unwrap)(e) {
+            Some(c) => map_cmds_arc(self.inner.update(&c), &self.wrap),
+            None => vec![],
+        }
+    }
+    fn view(&self) -> String {
+        self.inner.view()
+    }
+    fn set_focused(&mut self, focused: bool) {
+        self.inner.set_focused(focused)
+    }
+    fn measure(&self, constraints: crate::layout::BoxConstraints) -> crate::layout::Size {
+        self.inner.measure(constraints)
+    }
+    fn render(&self, area: crate::layout::Rect) -> String {
+        self.inner.render(area)
+    }
+    fn paint(&self, buf: &mut crate::buffer::Buffer, area: crate::layout::Rect) {
+        self.inner.paint(buf, area)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Key, SystemEvent};
+
+    #[derive(Eq, PartialEq, Clone, Debug)]
+    enum Parent {
+        Se(SystemEvent),
+        Child(SystemEvent),
+    }
+    impl Event for Parent {
+        fn from_system_event(se: SystemEvent) -> Self {
+            Parent::Se(se)
+        }
+        fn to_system_event(&self) -> Option<SystemEvent> {
+            match self {
+                Parent::Se(se) => Some(se.clone()),
+                _ => None,
+            }
+        }
+    }
+
+    struct Echo(Vec<SystemEvent>);
+    impl Model<SystemEvent> for Echo {
+        fn update(
+            &mut self,
+            e: &SystemEvent,
+        ) -> Vec<Box<dyn FnOnce() -> SystemEvent + Send + 'static>> {
+            self.0.push(e.clone());
+            let e = e.clone();
+            vec![Box::new(move || e)]
+        }
+        fn view(&self) -> String {
+            format!("{}", self.0.len())
+        }
+    }
+
+    #[test]
+    fn events_are_mapped() {
+        let mut child = Child::new(Echo(vec![]), Parent::Child, |e: &Parent| match e {
+            Parent::Child(e) => Some(e.clone()),
+            _ => None,
+        });
+        let key = SystemEvent::KeyPress(Key::Char('a'));
+        assert!(Model::update(&mut child, &Parent::Se(key.clone())).is_empty());
+        let cmds = Model::update(&mut child, &Parent::Child(key.clone()));
+        let events: Vec<Parent> = cmds.into_iter().map(|c| c()).collect();
+        assert_eq!(events, vec![Parent::Child(key.clone())]);
+        assert_eq!(child.inner().0, vec![key]);
+        assert_eq!(Model::view(&child), "1");
+    }
+}
@@ -0,0 +1,154 @@
+use crate::buffer::{Buffer, Cell};
+use crate::layout::{Rect, Size};
+use crate::style::Align;
+
+///Where a layer is drawn
+#[derive(Eq, PartialEq, Clone, Copy, Debug)]
+pub enum Placement {
+    ///At the given zero indexed column and row
+    Absolute(u16, u16),
+    ///Aligned to the edges or the center of the screen, moved by the given
+    ///number of columns and rows
+    Anchored(Align, Align, i16, i16),
+}
+
+///Text drawn over the layers below it. The layer is opaque: every cell of its
+///area hides what is under it, including the halves of wide characters.
+#[derive(Eq, PartialEq, Clone, Debug)]
+pub struct Layer {
+    content: String,
+    z: i32,
+    placement: Placement,
+    size: Option<Size>,
+}
+
+impl Layer {
+    ///Creates a layer at the top left of the screen with a z of 0
+    pub fn new<S: Into<String>>(content: S) -> Self {
+        Layer {
+            content: content.into(),
+            z: 0,
+            placement: Placement::Absolute(0, 0),
+            size: None,
+        }
+    }
+    ///Sets the order of the layer, layers with a higher z are drawn over the
+    ///ones with a lower z and layers with the same z are drawn in the order
+    ///they were added
+    pub fn z(mut self, z: i32) -> Self {
+        self.z = z;
+        self
+    }
+    ///Draws the layer at the given zero indexed column and row
+    pub fn at(mut self, x: u16, y: u16) -> Self {
+        self.placement = Placement::Absolute(x, y);
+        self
+    }
+    ///Sets where the layer is drawn
+    pub fn placement(mut self, placement: Placement) -> Self {
+        self.placement = placement;
+        self
+    }
+    ///Sets the size of the layer, by default it is the size of its content
+    pub fn size(mut self, width: u16, height: u16) -> Self {
+        self.size = Some(Size::new(width, height));
+        self
+    }
+    //The area of the layer on a screen of the given size
+    fn area(&self, screen: Size) -> Rect {
+        let size = self
+            .size
+            .unwrap_or_else(|| crate::layout::measure(&self.content));
+        let (x, y) = match self.placement {
+            Placement::Absolute(x, y) => (x, y),
+            Placement::Anchored(h, v, dx, dy) => {
+                let place = |align: Align, space: u16, len: u16, offset: i16| {
+                    let free = space.saturating_sub(len);
+                    let start = match align {
+                        Align::Start => 0,
+                        Align::Center => free / 2,
+                        Align::End => free,
+                    };
+                    start.saturating_add_signed(offset)
+                };
+                (
+                    place(h, screen.width, size.width, dx),
+                    place(v, screen.height, size.height, dy),
+                )
+            }
+        };
+        Rect::new(x, y, size.width, size.height)
+    }
+}
+
+///Stacks layers on top of each other so that dialogs, dropdowns and toasts can
+///float over the rest of the view
+#[derive(Eq, PartialEq, Clone, Debug)]
+pub struct Compositor {
+    size: Size,
+    layers: Vec<Layer>,
+}
+
+impl Compositor {
+    ///Creates a compositor for a screen of the given size
+    pub fn new(width: u16, height: u16) -> Self {
+        Compositor {
+            size: Size::new(width, height),
+            layers: Vec::new(),
+        }
+    }
+    ///Adds a layer
+    pub fn layer(mut self, layer: Layer) -> Self {
+        self.push(layer);
+        self
+    }
+    ///Adds a layer
+    pub fn push(&mut self, layer: Layer) {
+        self.layers.push(layer);
+    }
+    ///Draws the layers from the lowest to the highest z into a buffer, which
+    ///can be returned from *Model::rich_view* with *View::buffer*
+    pub fn compose(&self) -> Buffer {
+        let mut buf = Buffer::new(self.size.width, self.size.height);
+        let mut layers: Vec<&Layer> = self.layers.iter().collect();
+        layers.sort_by_key(|l| l.z);
+        for layer in layers {
+            let area = layer.area(self.size);
+            buf.fill(area, &Cell::default());
+            buf.set_styled(area, &layer.content);
+        }
+        buf
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn row(buf: &Buffer, y: u16) -> Vec<String> {
+        (0..buf.area().width)
+            .map(|x| buf.get(x, y).unwrap().symbol.clone())
+            .collect()
+    }
+
+    #[test]
+    fn layers_are_stacked() {
+        let buf = Compositor::new(6, 3)
+            .layer(
+                Layer::new("ab")
+                    .placement(Placement::Anchored(Align::Center, Align::End, 0, 0))
+                    .z(1),
+            )
+            .layer(Layer::new("日本語\r\n123456\r\nxxxxxx"))
+            .layer(Layer::new("cd").at(1, 0).z(2))
+            .compose();
+        assert_eq!(row(&buf, 0), vec![" ", "c", "d", " ", "語", ""]);
+        assert_eq!(row(&buf, 2), vec!["x", "x", "a", "b", "x", "x"]);
+    }
+    #[test]
+    fn anchored_offsets() {
+        let layer =
+            Layer::new("ab").placement(Placement::Anchored(Align::End, Align::Start, -1, 1));
+        assert_eq!(layer.area(Size::new(10, 5)), Rect::new(7, 1, 2, 1));
+    }
+}
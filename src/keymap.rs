@@ -0,0 +1,339 @@
+use crate::{Event, Key, SystemEvent};
+
+///Returns the name of a key as it is displayed in help text and written in
+///keymap configs
+pub fn key_name(k: &Key) -> String {
+    match k {
+        Key::Backspace => "backspace".to_string(),
+        Key::Left => "←".to_string(),
+        Key::Right => "→".to_string(),
+        Key::Up => "↑".to_string(),
+        Key::Down => "↓".to_string(),
+        Key::Home => "home".to_string(),
+        Key::End => "end".to_string(),
+        Key::PageUp => "pgup".to_string(),
+        Key::PageDown => "pgdown".to_string(),
+        Key::BackTab => "shift+tab".to_string(),
+        Key::Delete => "delete".to_string(),
+        Key::Insert => "insert".to_string(),
+        Key::F(n) => format!("f{}", n),
+        Key::Char(' ') => "space".to_string(),
+        Key::Char(',') => "comma".to_string(),
+        Key::Char('\n') => "enter".to_string(),
+        Key::Char('\t') => "tab".to_string(),
+        Key::Char(c) => c.to_string(),
+        Key::Alt(c) => format!("alt+{}", c),
+        Key::Ctrl(c) => format!("ctrl+{}", c),
+        Key::Null => "null".to_string(),
+        Key::Esc => "esc".to_string(),
+        _ => "?".to_string(),
+    }
+}
+
+///Parses a key name such as `q`, `ctrl+c`, `alt+x`, `enter`, `up` or `f5`
+pub fn parse_key(s: &str) -> Option<Key> {
+    let single = |s: &str| {
+        let mut chars = s.chars();
+        match (chars.next(), chars.next()) {
+            (Some(c), None) => Some(c),
+            _ => None,
+        }
+    };
+    if let Some(c) = s.strip_prefix("ctrl+") {
+        return single(c).map(Key::Ctrl);
+    }
+    if let Some(c) = s.strip_prefix("alt+") {
+        return single(c).map(Key::Alt);
+    }
+    Some(match s {
+        "backspace" => Key::Backspace,
+        "left" | "←" => Key::Left,
+        "right" | "→" => Key::Right,
+        "up" | "↑" => Key::Up,
+        "down" | "↓" => Key::Down,
+        "home" => Key::Home,
+        "end" => Key::End,
+        "pgup" => Key::PageUp,
+        "pgdown" => Key::PageDown,
+        "shift+tab" => Key::BackTab,
+        "delete" => Key::Delete,
+        "insert" => Key::Insert,
+        "space" => Key::Char(' '),
+        "comma" => Key::Char(','),
+        "enter" => Key::Char('\n'),
+        "tab" => Key::Char('\t'),
+        "esc" => Key::Esc,
+        s if s.len() > 1 && s.starts_with('f') => Key::F(s[1..].parse().ok()?),
+        s => Key::Char(single(s)?),
+    })
+}
+
+///A set of keys that trigger the same action and the help text that
+///describes them
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Binding {
+    keys: Vec<Key>,
+    help_key: String,
+    help_desc: String,
+    enabled: bool,
+}
+
+impl Binding {
+    ///Creates an enabled binding for *keys*. The keys shown in the help text
+    ///are the names of *keys* separated by slashes
+    pub fn new(keys: Vec<Key>) -> Self {
+        Binding {
+            help_key: Self::keys_help(&keys),
+            keys,
+            help_desc: String::new(),
+            enabled: true,
+        }
+    }
+    fn keys_help(keys: &[Key]) -> String {
+        keys.iter().map(key_name).collect::<Vec<_>>().join("/")
+    }
+    ///Sets the description shown in the help text
+    pub fn help<S: Into<String>>(mut self, desc: S) -> Self {
+        self.help_desc = desc.into();
+        self
+    }
+    ///Sets the keys shown in the help text, e.g. `↑/k`
+    pub fn help_key<S: Into<String>>(mut self, key: S) -> Self {
+        self.help_key = key.into();
+        self
+    }
+    ///Sets whether the binding is enabled, disabled bindings never match
+    pub fn enabled(mut self, enabled: bool) -> Self {
+        self.enabled = enabled;
+        self
+    }
+    ///Enables or disables the binding
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+    }
+    ///Replaces the keys of the binding and the keys shown in the help text
+    pub fn set_keys(&mut self, keys: Vec<Key>) {
+        self.help_key = Self::keys_help(&keys);
+        self.keys = keys;
+    }
+    ///The keys that trigger the binding
+    pub fn keys(&self) -> &[Key] {
+        &self.keys
+    }
+    ///The keys shown in the help text
+    pub fn get_help_key(&self) -> &str {
+        &self.help_key
+    }
+    ///The description shown in the help text
+    pub fn get_help(&self) -> &str {
+        &self.help_desc
+    }
+    ///Is the binding enabled
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+    ///Returns true if the binding is enabled and *k* is one of its keys
+    pub fn matches_key(&self, k: &Key) -> bool {
+        self.enabled && self.keys.contains(k)
+    }
+    ///Returns true if *e* is a press of one of the binding's keys
+    pub fn matches<E: Event>(&self, e: &E) -> bool {
+        match e.to_system_event() {
+            Some(SystemEvent::KeyPress(k)) => self.matches_key(&k),
+            _ => false,
+        }
+    }
+}
+
+///An error in a keymap config
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConfigError {
+    ///The one indexed line the error is on
+    pub line: usize,
+    ///What is wrong with the line
+    pub message: String,
+}
+
+impl std::fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+///Named actions and the bindings that trigger them, in the order they were
+///added
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct KeyMap {
+    bindings: Vec<(String, Binding)>,
+}
+
+impl KeyMap {
+    ///Creates an empty keymap
+    pub fn new() -> Self {
+        KeyMap {
+            bindings: Vec::new(),
+        }
+    }
+    ///Adds an action, replacing the binding if the action already exists
+    pub fn bind<S: Into<String>>(mut self, action: S, binding: Binding) -> Self {
+        self.set(action, binding);
+        self
+    }
+    ///Sets the binding of an action, adding the action if it does not exist
+    pub fn set<S: Into<String>>(&mut self, action: S, binding: Binding) {
+        let action = action.into();
+        match self.get_mut(&action) {
+            Some(b) => *b = binding,
+            None => self.bindings.push((action, binding)),
+        }
+    }
+    ///The binding of an action
+    pub fn get(&self, action: &str) -> Option<&Binding> {
+        self.bindings
+            .iter()
+            .find(|(a, _)| a == action)
+            .map(|(_, b)| b)
+    }
+    ///The binding of an action
+    pub fn get_mut(&mut self, action: &str) -> Option<&mut Binding> {
+        self.bindings
+            .iter_mut()
+            .find(|(a, _)| a == action)
+            .map(|(_, b)| b)
+    }
+    ///Returns true if *e* triggers the given action
+    pub fn matches<E: Event>(&self, action: &str, e: &E) -> bool {
+        self.get(action).is_some_and(|b| b.matches(e))
+    }
+    ///Returns the first action triggered by *e*
+    pub fn action<E: Event>(&self, e: &E) -> Option<&str> {
+        self.bindings
+            .iter()
+            .find(|(_, b)| b.matches(e))
+            .map(|(a, _)| a.as_str())
+    }
+    ///The actions and their bindings in the order they were added
+    pub fn bindings(&self) -> impl Iterator<Item = (&str, &Binding)> {
+        self.bindings.iter().map(|(a, b)| (a.as_str(), b))
+    }
+    ///Overrides the keys of the actions from a config with one action per
+    ///line, e.g.
+    ///```text
+    ///# comments start with a hash
+    ///up = up, k
+    ///quit = ctrl+c, q
+    ///help = none
+    ///```
+    ///`none` disables the action and the comma key is written `comma`.
+    ///Actions that are not in the keymap are an error so that typos do not go
+    ///unnoticed. Nothing is changed unless the whole config is valid.
+    pub fn apply_config(&mut self, config: &str) -> Result<(), ConfigError> {
+        let mut changes = Vec::new();
+        for (i, line) in config.lines().enumerate() {
+            let err = |message: String| ConfigError {
+                line: i + 1,
+                message,
+            };
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let (action, keys) = line
+                .split_once('=')
+                .ok_or_else(|| err("expected `action = keys`".to_string()))?;
+            let action = action.trim();
+            if self.get(action).is_none() {
+                return Err(err(format!("unknown action {:?}", action)));
+            }
+            if keys.trim() == "none" {
+                changes.push((action, None));
+                continue;
+            }
+            let keys = keys
+                .split(',')
+                .map(|k| {
+                    let k = k.trim();
+                    parse_key(k).ok_or_else(|| err(format!("unknown key {:?}", k)))
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            changes.push((action, Some(keys)));
+        }
+        for (action, keys) in changes {
+            let binding = self.get_mut(action).unwrap();
+            binding.set_enabled(keys.is_some());
+            if let Some(keys) = keys {
+                binding.set_keys(keys);
+            }
+        }
+        Ok(())
+    }
+    ///Reads a config file and applies it with *apply_config*
+    pub fn apply_config_file<P: AsRef<std::path::Path>>(&mut self, path: P) -> std::io::Result<()> {
+        let config = std::fs::read_to_string(path)?;
+        self.apply_config(&config)
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn keymap() -> KeyMap {
+        KeyMap::new()
+            .bind("up", Binding::new(vec![Key::Up, Key::Char('k')]).help("up"))
+            .bind("quit", Binding::new(vec![Key::Char('q')]).help("quit"))
+    }
+
+    #[test]
+    fn key_names_round_trip() {
+        for k in [
+            Key::Up,
+            Key::Char('k'),
+            Key::Char(' '),
+            Key::Char(','),
+            Key::Char('\n'),
+            Key::Ctrl('c'),
+            Key::Alt('x'),
+            Key::F(12),
+            Key::BackTab,
+            Key::Esc,
+        ] {
+            assert_eq!(parse_key(&key_name(&k)), Some(k));
+        }
+        assert_eq!(parse_key("ctrl+"), None);
+    }
+    #[test]
+    fn matching() {
+        let km = keymap();
+        let k = SystemEvent::KeyPress(Key::Char('k'));
+        assert!(km.matches("up", &k));
+        assert!(!km.matches("quit", &k));
+        assert_eq!(km.action(&k), Some("up"));
+        assert_eq!(km.action(&SystemEvent::WindowResize(1, 1)), None);
+        assert_eq!(km.get("up").unwrap().get_help_key(), "↑/k");
+    }
+    #[test]
+    fn config() {
+        let mut km = keymap();
+        km.apply_config("# comment\nup = ctrl+p, up\n\nquit = none\n")
+            .unwrap();
+        assert!(km.matches("up", &SystemEvent::KeyPress(Key::Ctrl('p'))));
+        assert!(!km.matches("up", &SystemEvent::KeyPress(Key::Char('k'))));
+        assert!(!km.matches("quit", &SystemEvent::KeyPress(Key::Char('q'))));
+        assert_eq!(
+            km.apply_config("down = j"),
+            Err(ConfigError {
+                line: 1,
+                message: "unknown action \"down\"".to_string()
+            })
+        );
+        assert!(km.apply_config("up = ctrl+pp").is_err());
+        assert!(km.apply_config("quit = q\nup = ctrl+pp").is_err());
+        assert!(!km.matches("quit", &SystemEvent::KeyPress(Key::Char('q'))));
+        km.apply_config("quit = comma").unwrap();
+        assert!(km.matches("quit", &SystemEvent::KeyPress(Key::Char(','))));
+    }
+}
@@ -0,0 +1,468 @@
+///The size of a widget in columns and rows
+#[derive(Eq, PartialEq, Clone, Copy, Debug, Default)]
+pub struct Size {
+    ///The number of columns
+    pub width: u16,
+    ///The number of rows
+    pub height: u16,
+}
+
+impl Size {
+    ///Creates a new size
+    pub fn new(width: u16, height: u16) -> Self {
+        Size { width, height }
+    }
+}
+
+///The smallest and largest sizes a parent allows a widget to take
+#[derive(Eq, PartialEq, Clone, Copy, Debug)]
+pub struct BoxConstraints {
+    ///The smallest size allowed
+    pub min: Size,
+    ///The largest size allowed
+    pub max: Size,
+}
+
+impl BoxConstraints {
+    ///Constraints that allow any size from nothing up to *max*
+    pub fn loose(max: Size) -> Self {
+        BoxConstraints {
+            min: Size::default(),
+            max,
+        }
+    }
+    ///Constraints that only allow *size*
+    pub fn tight(size: Size) -> Self {
+        BoxConstraints {
+            min: size,
+            max: size,
+        }
+    }
+    ///Returns the size closest to *size* that fits the constraints
+    pub fn constrain(&self, size: Size) -> Size {
+        Size {
+            width: size
+                .width
+                .clamp(self.min.width, self.max.width.max(self.min.width)),
+            height: size
+                .height
+                .clamp(self.min.height, self.max.height.max(self.min.height)),
+        }
+    }
+}
+
+///A rectangle on the screen, *x* and *y* are the zero indexed column and row
+///of its top left corner
+#[derive(Eq, PartialEq, Clone, Copy, Debug, Default)]
+pub struct Rect {
+    ///The column of the left edge
+    pub x: u16,
+    ///The row of the top edge
+    pub y: u16,
+    ///The number of columns
+    pub width: u16,
+    ///The number of rows
+    pub height: u16,
+}
+
+impl Rect {
+    ///Creates a new rectangle
+    pub fn new(x: u16, y: u16, width: u16, height: u16) -> Self {
+        Rect {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+    ///The size of the rectangle
+    pub fn size(&self) -> Size {
+        Size::new(self.width, self.height)
+    }
+}
+
+//Splits a view into its lines, views use both "\r\n" and "\n\r"
+pub(crate) fn lines(view: &str) -> Vec<&str> {
+    view.split('\n').map(|l| l.trim_matches('\r')).collect()
+}
+
+///Measures the lines of a view, this is what *Widget::measure* does unless
+///the widget knows better
+pub fn measure(view: &str) -> Size {
+    let lines = lines(view);
+    let width = lines.iter().map(|l| crate::style::width(l)).max();
+    Size::new(width.unwrap_or(0) as u16, lines.len() as u16)
+}
+
+///Draws the lines of a view into *area*. Lines that are too long are cut,
+///short lines are padded with spaces and missing lines are left blank so
+///that whatever was in the area before is overwritten.
+pub fn fit(view: &str, area: Rect) -> String {
+    let lines = lines(view);
+    let mut ret = String::new();
+    for row in 0..area.height {
+        let line =
+            crate::style::truncate(lines.get(row as usize).unwrap_or(&""), area.width as usize);
+        let pad = area.width as usize - crate::style::width(&line);
+        ret += &format!(
+            "{}{}{}",
+            termion::cursor::Goto(area.x + 1, area.y + row + 1),
+            line,
+            " ".repeat(pad)
+        );
+    }
+    ret
+}
+
+///The axis along which a *Flex* places its areas
+#[derive(Eq, PartialEq, Clone, Copy, Debug)]
+pub enum Direction {
+    ///Areas next to each other, left to right
+    Horizontal,
+    ///Areas under each other, top to bottom
+    Vertical,
+}
+
+///How much space an area of a *Flex* takes along its direction
+#[derive(Eq, PartialEq, Clone, Copy, Debug)]
+pub enum Constraint {
+    ///Exactly the given number of cells
+    Length(u16),
+    ///The given percentage of the space, from 0 to 100. Larger values are
+    ///treated as 100. The cell lost to rounding goes to the last percentage
+    ///area
+    Percentage(u16),
+    ///At least the given number of cells, grows like *Fill(1)*. When the
+    ///space is too small the other areas shrink first
+    Min(u16),
+    ///At most the given number of cells, grows like *Fill(1)* until it
+    ///reaches its size
+    Max(u16),
+    ///A share of the space left after the other constraints, weighted by the
+    ///given number
+    Fill(u16),
+}
+
+///Splits a rectangle into rows or columns. The areas it returns can be split
+///again with another *Flex* to build nested layouts.
+#[derive(Eq, PartialEq, Clone, Debug)]
+pub struct Flex {
+    direction: Direction,
+    constraints: Vec<Constraint>,
+    gap: u16,
+}
+
+impl Flex {
+    ///Creates a layout with one area per constraint
+    pub fn new(direction: Direction, constraints: Vec<Constraint>) -> Self {
+        Flex {
+            direction,
+            constraints,
+            gap: 0,
+        }
+    }
+    ///Sets the number of empty cells between the areas
+    pub fn gap(mut self, gap: u16) -> Self {
+        self.gap = gap;
+        self
+    }
+    //The size of every area along the direction of the layout
+    fn sizes(&self, total: u32) -> Vec<u32> {
+        let mut sizes: Vec<u32> = self
+            .constraints
+            .iter()
+            .map(|c| match c {
+                Constraint::Length(n) | Constraint::Min(n) => *n as u32,
+                Constraint::Percentage(p) => total * (*p).min(100) as u32 / 100,
+                Constraint::Max(_) | Constraint::Fill(_) => 0,
+            })
+            .collect();
+        let percentages = self
+            .constraints
+            .iter()
+            .enumerate()
+            .filter_map(|(i, c)| match c {
+                Constraint::Percentage(p) => Some((i, (*p).min(100) as u64)),
+                _ => None,
+            });
+        if let Some((last, _)) = percentages.clone().next_back() {
+            //Many percentages can add up to more than u32 can hold
+            let all = total as u64 * percentages.clone().map(|(_, p)| p).sum::<u64>() / 100;
+            let given: u64 = percentages.map(|(i, _)| sizes[i] as u64).sum();
+            sizes[last] += all.saturating_sub(given) as u32;
+        }
+        //When the space is too small percentages shrink first, then lengths
+        //and finally minimums, the last areas of each kind first
+        let mut over = sizes.iter().sum::<u32>().saturating_sub(total);
+        for shrinks in [
+            |c: &Constraint| matches!(c, Constraint::Percentage(_)),
+            |c: &Constraint| matches!(c, Constraint::Length(_)),
+            |c: &Constraint| matches!(c, Constraint::Min(_)),
+        ] {
+            for (size, _) in sizes
+                .iter_mut()
+                .zip(&self.constraints)
+                .rev()
+                .filter(|(_, c)| shrinks(c))
+            {
+                let cut = over.min(*size);
+                *size -= cut;
+                over -= cut;
+            }
+        }
+        let mut free = total - sizes.iter().sum::<u32>();
+        while free > 0 {
+            let growable: Vec<(usize, u32, u32)> = self
+                .constraints
+                .iter()
+                .enumerate()
+                .filter_map(|(i, c)| match c {
+                    Constraint::Min(_) => Some((i, 1, u32::MAX)),
+                    Constraint::Max(n) if sizes[i] < *n as u32 => Some((i, 1, *n as u32)),
+                    Constraint::Fill(w) if *w > 0 => Some((i, *w as u32, u32::MAX)),
+                    _ => None,
+                })
+                .collect();
+            let weights: u32 = growable.iter().map(|(_, w, _)| w).sum();
+            if growable.is_empty() {
+                break;
+            }
+            let mut given = 0;
+            for (i, w, max) in &growable {
+                let share = (free * w / weights).min(max - sizes[*i]);
+                sizes[*i] += share;
+                given += share;
+            }
+            //Whatever the division left over goes to the first areas
+            if given == 0 {
+                for (i, _, _) in growable.iter().take(free as usize) {
+                    sizes[*i] += 1;
+                    given += 1;
+                }
+            }
+            free -= given;
+        }
+        sizes
+    }
+    ///Splits *area* into one area per constraint
+    pub fn split(&self, area: Rect) -> Vec<Rect> {
+        let main = match self.direction {
+            Direction::Horizontal => area.width,
+            Direction::Vertical => area.height,
+        } as u32;
+        let gaps = self.gap as u32 * self.constraints.len().saturating_sub(1) as u32;
+        let mut offset = 0;
+        self.sizes(main.saturating_sub(gaps))
+            .into_iter()
+            .map(|size| {
+                let start = offset.min(main) as u16;
+                let size = size.min(main.saturating_sub(offset)) as u16;
+                offset += size as u32 + self.gap as u32;
+                match self.direction {
+                    Direction::Horizontal => Rect::new(area.x + start, area.y, size, area.height),
+                    Direction::Vertical => Rect::new(area.x, area.y + start, area.width, size),
+                }
+            })
+            .collect()
+    }
+    ///Splits *area* and draws each of the views into its area, views without
+    ///an area are dropped
+    pub fn fit(&self, area: Rect, views: &[&str]) -> String {
+        self.split(area)
+            .into_iter()
+            .zip(views)
+            .map(|(area, view)| fit(view, area))
+            .collect()
+    }
+}
+
+///The position of a child of a *Grid*, zero indexed, and the number of rows
+///and columns it spans
+#[derive(Eq, PartialEq, Clone, Copy, Debug)]
+pub struct Tile {
+    ///The first row of the tile
+    pub row: u16,
+    ///The first column of the tile
+    pub col: u16,
+    ///The number of rows the tile spans, a span of 0 is treated as 1
+    pub rows: u16,
+    ///The number of columns the tile spans, a span of 0 is treated as 1
+    pub cols: u16,
+}
+
+impl Tile {
+    ///A tile that takes a single cell of the grid
+    pub fn new(row: u16, col: u16) -> Self {
+        Tile {
+            row,
+            col,
+            rows: 1,
+            cols: 1,
+        }
+    }
+    ///Sets the number of rows and columns the tile spans
+    pub fn span(mut self, rows: u16, cols: u16) -> Self {
+        self.rows = rows.max(1);
+        self.cols = cols.max(1);
+        self
+    }
+}
+
+///Places tiles on a grid of row and column tracks. The tracks are sized like
+///the areas of a *Flex* and recomputed when the grid is resized, either with
+///*resize* or by passing it the events received by update.
+#[derive(Eq, PartialEq, Clone, Debug)]
+pub struct Grid {
+    columns: Flex,
+    rows: Flex,
+    column_tracks: Vec<Rect>,
+    row_tracks: Vec<Rect>,
+    area: Rect,
+}
+
+impl Grid {
+    ///Creates a grid with one track per constraint, the grid has no area
+    ///until it is resized
+    pub fn new(columns: Vec<Constraint>, rows: Vec<Constraint>) -> Self {
+        Grid {
+            columns: Flex::new(Direction::Horizontal, columns),
+            rows: Flex::new(Direction::Vertical, rows),
+            column_tracks: Vec::new(),
+            row_tracks: Vec::new(),
+            area: Rect::default(),
+        }
+    }
+    ///Sets the number of empty cells between the columns and between the rows
+    pub fn gap(mut self, column_gap: u16, row_gap: u16) -> Self {
+        self.columns = self.columns.gap(column_gap);
+        self.rows = self.rows.gap(row_gap);
+        self.resize(self.area);
+        self
+    }
+    ///The area the grid covers
+    pub fn area(&self) -> Rect {
+        self.area
+    }
+    ///Sets the area the grid covers and recomputes its tracks
+    pub fn resize(&mut self, area: Rect) {
+        self.area = area;
+        self.column_tracks = self.columns.split(area);
+        self.row_tracks = self.rows.split(area);
+    }
+    ///Resizes the grid to fill the window when *e* is a
+    ///*SystemEvent::WindowResize*, returns whether it was
+    pub fn update<E: crate::Event>(&mut self, e: &E) -> bool {
+        match e.to_system_event() {
+            Some(crate::SystemEvent::WindowResize(w, h)) => {
+                self.resize(Rect::new(0, 0, w, h));
+                true
+            }
+            _ => false,
+        }
+    }
+    ///The area of a tile, spans that go past the last track are cut
+    pub fn area_of(&self, tile: Tile) -> Rect {
+        let span = |tracks: &[Rect], start: u16, len: u16| {
+            let first = tracks.get(start as usize)?;
+            let last = tracks
+                .get(start as usize + len.max(1) as usize - 1)
+                .or(tracks.last())?;
+            Some((*first, *last))
+        };
+        let cols = span(&self.column_tracks, tile.col, tile.cols);
+        let rows = span(&self.row_tracks, tile.row, tile.rows);
+        match (cols, rows) {
+            (Some((left, right)), Some((top, bottom))) => Rect::new(
+                left.x,
+                top.y,
+                right.x + right.width - left.x,
+                bottom.y + bottom.height - top.y,
+            ),
+            _ => Rect::default(),
+        }
+    }
+    ///Draws each view into the area of its tile
+    pub fn fit(&self, tiles: &[(Tile, &str)]) -> String {
+        tiles
+            .iter()
+            .map(|(tile, view)| fit(view, self.area_of(*tile)))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn constrain() {
+        let c = BoxConstraints {
+            min: Size::new(2, 1),
+            max: Size::new(10, 3),
+        };
+        assert_eq!(c.constrain(Size::new(20, 0)), Size::new(10, 1));
+        assert_eq!(
+            BoxConstraints::tight(Size::new(4, 4)).constrain(Size::default()),
+            Size::new(4, 4)
+        );
+    }
+    #[test]
+    fn flex_split() {
+        use Constraint::*;
+        let area = Rect::new(0, 1, 20, 10);
+        let rows = Flex::new(Direction::Vertical, vec![Length(1), Fill(1), Length(1)]).split(area);
+        assert_eq!(rows[1], Rect::new(0, 2, 20, 8));
+        let cols = Flex::new(
+            Direction::Horizontal,
+            vec![Percentage(25), Fill(2), Fill(1), Max(2)],
+        )
+        .gap(1)
+        .split(rows[1]);
+        assert_eq!(
+            cols.iter().map(|r| (r.x, r.width)).collect::<Vec<_>>(),
+            vec![(0, 4), (5, 8), (14, 3), (18, 2)]
+        );
+        let squeezed =
+            Flex::new(Direction::Horizontal, vec![Length(5), Min(3)]).split(Rect::new(0, 0, 6, 1));
+        assert_eq!((squeezed[0].width, squeezed[1].width), (3, 3));
+        let halves = Flex::new(Direction::Horizontal, vec![Percentage(50), Percentage(50)])
+            .split(Rect::new(0, 0, 5, 1));
+        assert_eq!((halves[0].width, halves[1].width), (2, 3));
+        let huge = Flex::new(Direction::Horizontal, vec![Percentage(u16::MAX); 1000])
+            .split(Rect::new(0, 0, u16::MAX, 1));
+        assert_eq!(huge[0].width, u16::MAX);
+    }
+    #[test]
+    fn grid_spans() {
+        use Constraint::*;
+        let mut grid =
+            Grid::new(vec![Fill(1), Fill(1), Fill(1)], vec![Length(2), Fill(1)]).gap(1, 1);
+        assert!(grid.update(&crate::SystemEvent::WindowResize(11, 10)));
+        assert_eq!(grid.area_of(Tile::new(0, 1)), Rect::new(4, 0, 3, 2));
+        assert_eq!(
+            grid.area_of(Tile::new(0, 1).span(2, 2)),
+            Rect::new(4, 0, 7, 10)
+        );
+        assert_eq!(
+            grid.area_of(Tile::new(1, 2).span(3, 3)),
+            Rect::new(8, 3, 3, 7)
+        );
+        assert_eq!(grid.area_of(Tile::new(5, 0)), Rect::default());
+        let empty = Tile {
+            row: 0,
+            col: 1,
+            rows: 0,
+            cols: 0,
+        };
+        assert_eq!(grid.area_of(empty), Rect::new(4, 0, 3, 2));
+        assert!(!grid.update(&crate::SystemEvent::Quit));
+    }
+    #[test]
+    fn fit_view() {
+        assert_eq!(measure("ab\r\nabc"), Size::new(3, 2));
+        assert_eq!(
+            fit("abc\r\nd", Rect::new(1, 2, 2, 3)),
+            "\x1b[3;2Hab\x1b[4;2Hd \x1b[5;2H  "
+        );
+    }
+}
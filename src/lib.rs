@@ -1,17 +1,45 @@
+///A grid of styled cells that can be drawn instead of a string
+pub mod buffer;
+mod cast;
+mod clipboard;
+///Helpers for embedding models and widgets whose events differ from the parent's
+pub mod compose;
+///Layers drawn over each other for popups and dialogs
+pub mod compositor;
+///Named key bindings with help text
+pub mod keymap;
+///Sizes, constraints and areas used to fit widgets into the screen
+pub mod layout;
+///A debug log that writes to a file instead of the screen
+pub mod log;
+///Middleware that can observe, change, drop or inject events before they reach update
+pub mod middleware;
+///Recording of input events and command results so that sessions can be replayed
+pub mod record;
+///A model that holds a navigation stack of screens
+pub mod router;
+///Matching of multi-key sequences such as `gg` or `ctrl+x ctrl+s`
+pub mod sequence;
+mod signals;
 ///The module responsible for styling
 pub mod style;
+///The structured output of a model
+pub mod view;
 ///A module that adds some prdefined widgets
 pub mod widgets;
 use std::io::{stdin, stdout, Write};
-use std::sync::mpsc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{mpsc, Arc, Mutex};
 use termion::input::TermRead;
 use termion::raw::IntoRawMode;
 
+#[cfg(feature = "derive")]
+pub use rubble_tea_derive::Event;
 pub use termion::event::Key;
 pub use termion::event::MouseButton;
 pub use termion::terminal_size;
 ///System events are the set of events that all models should support
-#[derive(Eq, PartialEq, Clone)]
+#[derive(Eq, PartialEq, Clone, Debug)]
 pub enum SystemEvent {
     ///When a key has been pressed
     KeyPress(Key),
@@ -23,10 +51,40 @@ pub enum SystemEvent {
     MouseHold(u16, u16),
     ///When the window has been resized
     WindowResize(u16, u16),
-    ///This causes the main loop to break, usually emmited from update.
+    ///Asks the terminal to store the string in the given selection, usually
+    ///emmited from a command created by *Cmd::copy_to_clipboard*
+    SetClipboard(Selection, String),
+    ///Asks the terminal to send the contents of the given selection back as a
+    ///*Clipboard* event. Many terminals do not allow this in which case no
+    ///event is received
+    RequestClipboard(Selection),
+    ///The contents of a selection sent by the terminal
+    Clipboard(Selection, String),
+    ///A tick of the widget with the given id, the second field is the
+    ///generation of the widget's ticks so that ticks scheduled before a
+    ///pause can be told apart from the current ones. See *widgets::new_id*
+    Tick(u64, u64),
+    ///The process received SIGINT
+    Interrupt,
+    ///The process received SIGTERM
+    Terminate,
+    ///The process received SIGHUP, usually because the terminal was closed
+    Hangup,
+    ///The process received SIGQUIT
+    QuitSignal,
+    ///This causes the main loop to break, usually emmited from update
     Quit,
 }
 
+///The selections that the terminal can copy to
+#[derive(Eq, PartialEq, Clone, Debug)]
+pub enum Selection {
+    ///The system clipboard
+    Clipboard,
+    ///The primary selection (the text pasted with the middle mouse button on X11)
+    Primary,
+}
+
 ///This trait allows the user to create custom events.
 ///*SystemEvent* implements this trait meaning that if the programer is content
 ///with the default events, they don't have to create their own wrapper.
@@ -46,6 +104,11 @@ pub trait Model<E: Event> {
     fn update(&mut self, e: &E) -> Vec<Box<dyn FnOnce() -> E + Send + 'static>>;
     ///This method turns the program's state into a string and returns the result
     fn view(&self) -> String;
+    ///Like *view* but can also describe the cursor and the window title.
+    ///The runtime calls this method, by default it wraps the result of *view*
+    fn rich_view(&self) -> view::View {
+        view::View::new(self.view())
+    }
 }
 
 impl Event for SystemEvent {
@@ -57,10 +120,93 @@ impl Event for SystemEvent {
     }
 }
 
-fn watch_input<E: Event>(tx: mpsc::Sender<E>) {
-    let stdin = stdin();
+///Constructors for commands that ask the runtime to do something on the
+///model's behalf
+pub struct Cmd;
+
+impl Cmd {
+    ///Copies *text* into the system clipboard using an OSC 52 sequence. This
+    ///works over SSH as long as the terminal supports OSC 52
+    pub fn copy_to_clipboard<E: Event>(text: String) -> Box<dyn FnOnce() -> E + Send + 'static> {
+        Cmd::copy_to(Selection::Clipboard, text)
+    }
+    ///Copies *text* into the given selection
+    pub fn copy_to<E: Event>(
+        selection: Selection,
+        text: String,
+    ) -> Box<dyn FnOnce() -> E + Send + 'static> {
+        Box::new(move || E::from_system_event(SystemEvent::SetClipboard(selection, text)))
+    }
+    ///Asks the terminal for the contents of the given selection, the result
+    ///is delivered to update as a *SystemEvent::Clipboard*
+    pub fn request_clipboard<E: Event>(
+        selection: Selection,
+    ) -> Box<dyn FnOnce() -> E + Send + 'static> {
+        Box::new(move || E::from_system_event(SystemEvent::RequestClipboard(selection)))
+    }
+}
+
+//The messages received by the main loop
+enum Message<E> {
+    Event(E),
+    //The replayed recording has no more events, only sent when running headless
+    ReplayEnded,
+}
+
+//Sends input events and command results to the main loop, writing them to
+//the recording first when the program is being recorded
+struct EventSender<E> {
+    tx: mpsc::Sender<Message<E>>,
+    recorder: Option<Arc<Mutex<record::Recorder>>>,
+    //Set while a recording with command results is replayed, the system
+    //events returned by commands are then taken from the recording instead
+    replaying: Arc<AtomicBool>,
+}
+
+impl<E: Event> EventSender<E> {
+    //Returns false if the main loop has stopped
+    fn send(&self, se: SystemEvent) -> bool {
+        self.send_from(record::Source::Input, se)
+    }
+    fn send_from(&self, source: record::Source, se: SystemEvent) -> bool {
+        if let Some(r) = &self.recorder {
+            r.lock().unwrap().record(source, &se);
+        }
+        self.tx
+            .send(Message::Event(E::from_system_event(se)))
+            .is_ok()
+    }
+    //Sends the event returned by a command
+    fn send_result(&self, e: E) {
+        if let Some(se) = e.to_system_event() {
+            if self.replaying.load(Ordering::SeqCst) && record::recordable(&se) {
+                return;
+            }
+            if let Some(r) = &self.recorder {
+                r.lock().unwrap().record(record::Source::Command, &se);
+            }
+        }
+        _ = self.tx.send(Message::Event(e));
+    }
+}
+
+impl<E> Clone for EventSender<E> {
+    fn clone(&self) -> Self {
+        EventSender {
+            tx: self.tx.clone(),
+            recorder: self.recorder.clone(),
+            replaying: self.replaying.clone(),
+        }
+    }
+}
+
+fn watch_input<E: Event>(input: EventSender<E>) {
+    let reply_input = input.clone();
+    let stdin = clipboard::OscFilter::new(stdin(), move |e| {
+        reply_input.send(e);
+    });
     for i in stdin.events() {
-        tx.send(E::from_system_event(match i.unwrap() {
+        input.send(match i.unwrap() {
             termion::event::Event::Mouse(me) => match me {
                 termion::event::MouseEvent::Press(k, x, y) => {
                     SystemEvent::MousePress(k, x - 1, y - 1)
@@ -72,62 +218,439 @@ fn watch_input<E: Event>(tx: mpsc::Sender<E>) {
             },
             termion::event::Event::Key(k) => SystemEvent::KeyPress(k),
             termion::event::Event::Unsupported(_) => continue,
-        }))
-        .unwrap();
+        });
     }
 }
 
-fn watch_resize<E: Event>(tx: mpsc::Sender<E>) {
+fn watch_resize<E: Event>(input: EventSender<E>) {
     let (mut ow, mut oh) = termion::terminal_size().unwrap();
-    tx.send(E::from_system_event(SystemEvent::WindowResize(ow, oh)))
-        .unwrap();
+    input.send(SystemEvent::WindowResize(ow, oh));
     loop {
         std::thread::sleep(std::time::Duration::new(0, 500000000));
         let (nw, nh) = termion::terminal_size().unwrap();
         if nw != ow || nh != oh {
-            tx.send(E::from_system_event(SystemEvent::WindowResize(nw, nh)))
-                .unwrap();
-            (ow, oh) = (ow, oh)
+            input.send(SystemEvent::WindowResize(nw, nh));
+            (ow, oh) = (nw, nh)
         }
     }
 }
 
-///Starts the event listeners and the main program loop
+//Sends the recorded events and command results at the same pace at which
+//they were recorded
+fn replay<E: Event>(recording: &record::Recording, events: &EventSender<E>) {
+    let start = std::time::Instant::now();
+    for (time, source, e) in recording.timeline() {
+        if let Some(d) = time.checked_sub(start.elapsed()) {
+            std::thread::sleep(d);
+        }
+        if !events.send_from(source, e.clone()) {
+            break;
+        }
+    }
+    //Once the recording is over commands deliver their own results again
+    events.replaying.store(false, Ordering::SeqCst);
+}
+
+fn spawn_cmd<E: Event>(events: &EventSender<E>, c: Box<dyn FnOnce() -> E + Send + 'static>) {
+    let events = events.clone();
+    std::thread::spawn(move || events.send_result(c()));
+}
+
+///The runtime's configuration. *run* uses the default configuration, a
+///*Program* is only needed when the defaults should be changed.
+pub struct Program<E: Event> {
+    quit_on_signal: bool,
+    record: Option<std::path::PathBuf>,
+    replay: Option<record::Recording>,
+    cast: Option<std::path::PathBuf>,
+    middleware: Vec<Box<dyn middleware::Middleware<E>>>,
+    frame_interval: Option<std::time::Duration>,
+}
+
+impl<E: Event> Program<E> {
+    ///Creates a program with the default configuration
+    pub fn new() -> Self {
+        Program {
+            quit_on_signal: true,
+            record: None,
+            replay: None,
+            cast: None,
+            middleware: Vec::new(),
+            frame_interval: None,
+        }
+    }
+    ///When *quit* is true (the default) the main loop breaks after the
+    ///*Interrupt*, *Terminate*, *Hangup* and *QuitSignal* events have been
+    ///passed to update, restoring the terminal. When it is false the model is
+    ///expected to emmit *SystemEvent::Quit* itself
+    pub fn quit_on_signal(mut self, quit: bool) -> Self {
+        self.quit_on_signal = quit;
+        self
+    }
+    ///Records every input event (key presses, mouse events, resizes, signals
+    ///and clipboard replies) and every system event returned by a command,
+    ///along with the time it was received, to the file at *path*. The file
+    ///can be loaded with *record::Recording::load*
+    pub fn record<P: Into<std::path::PathBuf>>(mut self, path: P) -> Self {
+        self.record = Some(path.into());
+        self
+    }
+    ///Feeds the events of *recording* to the model at the pace they were
+    ///recorded at instead of reading the terminal. Once the recording is over
+    ///*run* goes back to reading the terminal while *run_headless* returns
+    ///without waiting for the commands that are still running. The system
+    ///events returned by commands are taken from the recording so that the
+    ///frames match the recorded session, see *record::Recording*
+    pub fn replay(mut self, recording: record::Recording) -> Self {
+        self.replay = Some(recording);
+        self
+    }
+    ///Writes every frame drawn by the program to an asciicast v2 file at
+    ///*path* so that it can be played back with standard players such as
+    ///asciinema
+    pub fn cast<P: Into<std::path::PathBuf>>(mut self, path: P) -> Self {
+        self.cast = Some(path.into());
+        self
+    }
+    ///Adds a middleware to the end of the chain that every event goes through
+    ///before reaching update
+    pub fn middleware<M: middleware::Middleware<E> + 'static>(mut self, m: M) -> Self {
+        self.middleware.push(Box::new(m));
+        self
+    }
+    ///Limits the number of frames drawn per second to *fps*. Events that
+    ///arrive in bursts (timer ticks, mouse movement, pasted text) are all
+    ///passed to update before a single frame is drawn. By default a frame is
+    ///drawn after every event
+    pub fn max_fps(mut self, fps: u32) -> Self {
+        self.frame_interval = Some(std::time::Duration::from_secs(1) / fps.max(1));
+        self
+    }
+    fn event_sender(&self, tx: &mpsc::Sender<Message<E>>) -> EventSender<E> {
+        let recorder = self
+            .record
+            .as_ref()
+            .and_then(|path| match record::Recorder::create(path) {
+                Ok(r) => Some(Arc::new(Mutex::new(r))),
+                Err(e) => {
+                    eprintln!("could not create the recording: {}", e);
+                    None
+                }
+            });
+        //Set before any command runs so that no live result slips in before
+        //the replay starts
+        let replaying = self
+            .replay
+            .as_ref()
+            .is_some_and(|r| !r.results().is_empty());
+        EventSender {
+            tx: tx.clone(),
+            recorder,
+            replaying: Arc::new(AtomicBool::new(replaying)),
+        }
+    }
+    fn cast_writer(&self) -> Option<cast::CastWriter<std::io::BufWriter<std::fs::File>>> {
+        let path = self.cast.as_ref()?;
+        let (w, h) = termion::terminal_size().unwrap_or((80, 24));
+        match cast::CastWriter::create(path, w, h) {
+            Ok(c) => Some(c),
+            Err(e) => {
+                eprintln!("could not create the asciicast file: {}", e);
+                None
+            }
+        }
+    }
+    ///Starts the event listeners and the main program loop
+    pub fn run<M: Model<E>>(
+        &mut self,
+        model: &mut M,
+        cmds: Vec<Box<dyn FnOnce() -> E + Send + 'static>>,
+    ) {
+        if let Err(e) = log::from_env() {
+            eprintln!("could not open the log file: {}", e);
+        }
+        let (tx, rx) = mpsc::channel();
+        let input = self.event_sender(&tx);
+        let cast = self.cast_writer();
+        //The handlers are installed before entering raw mode so that no signal
+        //can kill the program without the terminal being restored
+        let signals = {
+            let input = input.clone();
+            signals::watch(move |se| _ = input.send(se))
+        };
+        let mut stdout = termion::input::MouseTerminal::from(stdout().into_raw_mode().unwrap());
+        {
+            let input = input.clone();
+            let recording = self.replay.clone();
+            std::thread::spawn(move || {
+                if let Some(recording) = recording {
+                    replay(&recording, &input);
+                }
+                {
+                    let input = input.clone();
+                    std::thread::spawn(move || watch_resize(input));
+                }
+                watch_input(input)
+            });
+        }
+        self.event_loop(model, cmds, (input, rx), &mut stdout, cast, &mut |_| ());
+        drop(signals);
+        //The model might have hidden the cursor. This fails when the terminal
+        //has hung up, in which case there is nothing left to restore
+        _ = write!(stdout, "{}", termion::cursor::Show);
+        _ = stdout.flush();
+    }
+    ///Runs the model without a terminal, feeding it the events of the
+    ///recording set with *replay*. Commands are executed as they would be by
+    ///*run*. Returns the output of *view* for every frame that would have
+    ///been drawn, once the recording is over or the model quits. Without a
+    ///recording this only returns once the model emmits *SystemEvent::Quit*
+    pub fn run_headless<M: Model<E>>(
+        &mut self,
+        model: &mut M,
+        cmds: Vec<Box<dyn FnOnce() -> E + Send + 'static>>,
+    ) -> Vec<String> {
+        let (tx, rx) = mpsc::channel();
+        let events = self.event_sender(&tx);
+        let cast = self.cast_writer();
+        if let Some(recording) = self.replay.clone() {
+            let events = events.clone();
+            std::thread::spawn(move || {
+                replay(&recording, &events);
+                _ = events.tx.send(Message::ReplayEnded);
+            });
+        }
+        let mut frames = Vec::new();
+        self.event_loop(
+            model,
+            cmds,
+            (events, rx),
+            &mut std::io::sink(),
+            cast,
+            &mut |f| frames.push(f.to_string()),
+        );
+        frames
+    }
+    fn event_loop<M: Model<E>, W: Write>(
+        &mut self,
+        model: &mut M,
+        cmds: Vec<Box<dyn FnOnce() -> E + Send + 'static>>,
+        (sender, rx): (EventSender<E>, mpsc::Receiver<Message<E>>),
+        out: &mut W,
+        mut cast: Option<cast::CastWriter<std::io::BufWriter<std::fs::File>>>,
+        on_frame: &mut dyn FnMut(&str),
+    ) {
+        for c in cmds {
+            spawn_cmd(&sender, c);
+        }
+        let mut last_view: Option<view::View> = None;
+        let mut last_render: Option<std::time::Instant> = None;
+        let mut hung_up = false;
+        //We are guaranteed to recive at least one event on startup (the resize event)
+        while let Ok(msg) = rx.recv() {
+            let mut frame = String::new();
+            let mut quit = false;
+            let mut changed = false;
+            //With a frame rate limit every event that arrives before the next
+            //frame is due is handled before rendering
+            let deadline = last_render.zip(self.frame_interval).map(|(t, d)| t + d);
+            let mut next = Some(msg);
+            while let Some(msg) = next.take() {
+                let events = match msg {
+                    Message::Event(i) => {
+                        let events = middleware::intercept(&mut self.middleware, i);
+                        for m in &mut self.middleware {
+                            for c in m.take_commands() {
+                                spawn_cmd(&sender, c);
+                            }
+                        }
+                        events
+                    }
+                    Message::ReplayEnded => {
+                        quit = true;
+                        break;
+                    }
+                };
+                for i in events {
+                    changed = true;
+                    let se = i.to_system_event();
+                    let mut cmds = model.update(&i);
+                    for m in &mut self.middleware {
+                        m.commands(&i, &mut cmds);
+                    }
+                    match &se {
+                        Some(se) => log!("event {:?} returned {} commands", se, cmds.len()),
+                        None => log!("custom event returned {} commands", cmds.len()),
+                    }
+                    for c in cmds {
+                        spawn_cmd(&sender, c);
+                    }
+                    match &se {
+                        Some(SystemEvent::SetClipboard(s, text)) => {
+                            frame += &clipboard::set_sequence(s, text)
+                        }
+                        Some(SystemEvent::RequestClipboard(s)) => {
+                            frame += &clipboard::request_sequence(s)
+                        }
+                        Some(SystemEvent::WindowResize(w, h)) => {
+                            //The old frame has to be redrawn for the new size
+                            last_view = None;
+                            if let Some(cast) = &mut cast {
+                                cast.resize(*w, *h);
+                            }
+                        }
+                        Some(SystemEvent::Quit) => quit = true,
+                        Some(SystemEvent::Hangup) => {
+                            hung_up = true;
+                            quit |= self.quit_on_signal;
+                        }
+                        Some(
+                            SystemEvent::Interrupt
+                            | SystemEvent::Terminate
+                            | SystemEvent::QuitSignal,
+                        ) => quit |= self.quit_on_signal,
+                        _ => (),
+                    }
+                }
+                if quit {
+                    break;
+                }
+                if let Some(deadline) = deadline {
+                    let now = std::time::Instant::now();
+                    if now < deadline {
+                        next = rx.recv_timeout(deadline - now).ok();
+                    }
+                }
+            }
+            //Nothing could have changed if all of the events were dropped
+            if changed {
+                let view = model.rich_view();
+                //Repainting an identical view only causes flickering
+                if last_view.as_ref() != Some(&view) {
+                    frame += &view::draw(&view, last_view.as_ref());
+                    on_frame(&view.content);
+                    last_view = Some(view);
+                    last_render = Some(std::time::Instant::now());
+                }
+            }
+            if !frame.is_empty() {
+                let written = out.write_all(frame.as_bytes()).and_then(|()| out.flush());
+                //Writing to a terminal that has hung up fails with EIO
+                if !hung_up {
+                    written.unwrap();
+                }
+                if let Some(cast) = &mut cast {
+                    cast.output(&frame);
+                }
+            }
+            if quit {
+                break;
+            }
+        }
+    }
+}
+
+impl<E: Event> Default for Program<E> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+///Starts the event listeners and the main program loop with the default
+///configuration
 pub fn run<E: Event, M: Model<E>>(
     model: &mut M,
     cmds: Vec<Box<dyn FnOnce() -> E + Send + 'static>>,
 ) {
-    let mut stdout = termion::input::MouseTerminal::from(stdout().into_raw_mode().unwrap());
-    let (tx, rx): (mpsc::Sender<E>, mpsc::Receiver<E>) = mpsc::channel();
-    {
-        let tx = tx.clone();
-        std::thread::spawn(move || watch_input(tx));
-    }
-    {
-        let tx = tx.clone();
-        std::thread::spawn(move || watch_resize(tx));
-    }
-    for c in cmds {
-        let tx = tx.clone();
-        std::thread::spawn(move || tx.send(c()));
-    }
-    //We are guaranteed to recive at least one event on startup (the resize event)
-    for i in rx.iter() {
-        for c in model.update(&i) {
-            let tx = tx.clone();
-            std::thread::spawn(move || tx.send(c()));
-        }
-        write!(
-            stdout,
-            "{}{}{}",
-            termion::clear::All,
-            termion::cursor::Goto(1, 1),
-            model.view()
-        )
-        .unwrap();
-        stdout.flush().unwrap();
-        if Some(SystemEvent::Quit) == i.to_system_event() {
-            break;
+    Program::new().run(model, cmds)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    //A model counting the presses of '+', shared by the runtime tests
+    pub(crate) struct Counter(pub(crate) u32);
+    impl Model<SystemEvent> for Counter {
+        fn update(
+            &mut self,
+            e: &SystemEvent,
+        ) -> Vec<Box<dyn FnOnce() -> SystemEvent + Send + 'static>> {
+            if let SystemEvent::KeyPress(Key::Char('+')) = e {
+                self.0 += 1;
+            }
+            vec![]
+        }
+        fn view(&self) -> String {
+            self.0.to_string()
         }
     }
+
+    //A recording of a resize followed by the given keys, one per millisecond
+    pub(crate) fn keys(keys: &str) -> record::Recording {
+        let mut events = vec![(Duration::ZERO, SystemEvent::WindowResize(80, 24))];
+        for (i, c) in keys.chars().enumerate() {
+            events.push((
+                Duration::from_millis(i as u64 + 1),
+                SystemEvent::KeyPress(Key::Char(c)),
+            ));
+        }
+        record::Recording::new(events)
+    }
+
+    #[test]
+    fn identical_frames_are_skipped() {
+        let frames = Program::new()
+            .replay(keys("+-+"))
+            .run_headless(&mut Counter(0), vec![]);
+        assert_eq!(frames, ["0", "1", "2"]);
+    }
+    #[test]
+    fn bursts_are_coalesced() {
+        let frames = Program::new()
+            .max_fps(10)
+            .replay(keys("+++"))
+            .run_headless(&mut Counter(0), vec![]);
+        assert_eq!(frames, ["0", "3"]);
+    }
+    //A writer that fails like a terminal that has hung up
+    struct HungUp;
+    impl Write for HungUp {
+        fn write(&mut self, _: &[u8]) -> std::io::Result<usize> {
+            Err(std::io::Error::from_raw_os_error(libc::EIO))
+        }
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn hangup_ignores_write_errors() {
+        let (tx, rx) = mpsc::channel();
+        tx.send(Message::Event(SystemEvent::Hangup)).unwrap();
+        let mut program = Program::new();
+        let events = program.event_sender(&tx);
+        let mut frames = Vec::new();
+        program.event_loop(
+            &mut Counter(0),
+            vec![],
+            (events, rx),
+            &mut HungUp,
+            None,
+            &mut |f| frames.push(f.to_string()),
+        );
+        assert_eq!(frames, ["0"]);
+    }
+    #[test]
+    fn middleware_drops_events() {
+        let frames = Program::new()
+            .middleware(middleware::from_fn(|e| match e {
+                SystemEvent::KeyPress(Key::Char('+')) => vec![],
+                e => vec![e],
+            }))
+            .replay(keys("++"))
+            .run_headless(&mut Counter(0), vec![]);
+        assert_eq!(frames, ["0"]);
+    }
 }
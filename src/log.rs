@@ -0,0 +1,83 @@
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::sync::Mutex;
+
+///The environment variable that *from_env* reads the log file's path from
+pub const LOG_ENV_VAR: &str = "RUBBLE_TEA_LOG";
+
+static LOG_FILE: Mutex<Option<File>> = Mutex::new(None);
+
+///Starts appending log messages to the file at *path*, creating it if needed
+pub fn to_file<P: AsRef<std::path::Path>>(path: P) -> std::io::Result<()> {
+    let file = OpenOptions::new().create(true).append(true).open(path)?;
+    *LOG_FILE.lock().unwrap() = Some(file);
+    Ok(())
+}
+
+///Starts logging to the file named by the *RUBBLE_TEA_LOG* environment
+///variable. Nothing happens if the variable is not set. *Program::run* calls
+///this on startup so setting the variable is enough to enable logging.
+pub fn from_env() -> std::io::Result<()> {
+    match std::env::var_os(LOG_ENV_VAR) {
+        Some(path) if LOG_FILE.lock().unwrap().is_none() => to_file(path),
+        _ => Ok(()),
+    }
+}
+
+///Stops logging and closes the log file
+pub fn disable() {
+    *LOG_FILE.lock().unwrap() = None;
+}
+
+///Returns true if log messages are being written somewhere
+pub fn enabled() -> bool {
+    LOG_FILE.lock().unwrap().is_some()
+}
+
+///Writes a line to the log file if logging is enabled. The *log!* macro
+///should usually be used instead.
+pub fn write(args: std::fmt::Arguments) {
+    if let Some(file) = LOG_FILE.lock().unwrap().as_mut() {
+        let time = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default();
+        //Failing to log should never take the program down
+        let _ = writeln!(
+            file,
+            "[{}.{:03}] {}",
+            time.as_secs(),
+            time.subsec_millis(),
+            args
+        );
+    }
+}
+
+///Writes a formatted line to the debug log. Unlike *println!* this does not
+///touch the screen, so it is safe to use inside *update* and *view*.
+#[macro_export]
+macro_rules! log {
+    ($($arg:tt)*) => {
+        $crate::log::write(format_args!($($arg)*))
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn log_to_file() {
+        let path = std::env::temp_dir().join(format!("rubble_tea_{}.log", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        crate::log!("not logged");
+        to_file(&path).unwrap();
+        assert!(enabled());
+        crate::log!("hello {}", 42);
+        disable();
+        crate::log!("not logged either");
+        let contents = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(contents.lines().count(), 1);
+        assert!(contents.ends_with("] hello 42\n"));
+    }
+}
@@ -0,0 +1,230 @@
+use crate::{Event, Key, SystemEvent};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+///Middleware sits between the runtime and *Model::update*. Every event goes
+///through the chain of middleware added with *Program::middleware* in order
+///before it reaches update.
+pub trait Middleware<E: Event> {
+    ///Handles an event before it reaches update and returns the events that
+    ///should be passed on. Returning an empty vector drops the event,
+    ///returning a different event transforms it and returning more than one
+    ///event injects the extra events. The returned events go through the rest
+    ///of the chain in order.
+    fn intercept(&mut self, e: E) -> Vec<E> {
+        vec![e]
+    }
+    ///Inspects the commands that update returned for the event *e*. Commands
+    ///can be added or removed from *cmds*.
+    fn commands(&mut self, _e: &E, _cmds: &mut Vec<Box<dyn FnOnce() -> E + Send + 'static>>) {}
+    ///Returns the commands the middleware wants to run, called after every
+    ///*intercept*. This lets a middleware start timers even when it drops
+    ///the event.
+    fn take_commands(&mut self) -> Vec<Box<dyn FnOnce() -> E + Send + 'static>> {
+        vec![]
+    }
+}
+
+///A middleware made out of a closure, created by *from_fn*
+pub struct FnMiddleware<F>(F);
+
+///Creates a middleware out of a closure that works like *Middleware::intercept*
+pub fn from_fn<E: Event, F: FnMut(E) -> Vec<E>>(f: F) -> FnMiddleware<F> {
+    FnMiddleware(f)
+}
+
+impl<E: Event, F: FnMut(E) -> Vec<E>> Middleware<E> for FnMiddleware<F> {
+    fn intercept(&mut self, e: E) -> Vec<E> {
+        (self.0)(e)
+    }
+}
+
+///Turns presses of any of the given keys into *SystemEvent::Quit*
+pub struct QuitKeys(pub Vec<Key>);
+
+impl<E: Event> Middleware<E> for QuitKeys {
+    fn intercept(&mut self, e: E) -> Vec<E> {
+        match e.to_system_event() {
+            Some(SystemEvent::KeyPress(k)) if self.0.contains(&k) => {
+                vec![E::from_system_event(SystemEvent::Quit)]
+            }
+            _ => vec![e],
+        }
+    }
+}
+
+///The largest number of times a *KeyMacro* is played by a single count
+pub const MAX_MACRO_COUNT: usize = 1000;
+
+///A handle to the state of a *KeyMacro*, meant to be kept by the model so
+///that it can show that a macro is being recorded
+#[derive(Clone, Default)]
+pub struct MacroStatus(Arc<AtomicBool>);
+
+impl MacroStatus {
+    ///Is a macro being recorded
+    pub fn is_recording(&self) -> bool {
+        self.0.load(Ordering::SeqCst)
+    }
+}
+
+///Records key presses and plays them back. Pressing the record key starts
+///recording, pressing it again stops it. Pressing the play key sends the
+///recorded keys to update as if they were typed again. When counts are
+///enabled, digits typed before the play key set the number of times the
+///macro is played, at most *MAX_MACRO_COUNT* times; other keys following
+///the digits are passed on with them.
+pub struct KeyMacro {
+    record_key: Key,
+    play_key: Key,
+    counts: bool,
+    count: String,
+    keys: Vec<Key>,
+    status: MacroStatus,
+}
+
+impl KeyMacro {
+    ///Creates a new macro recorder with
+    ///*record_key* being the key that starts and stops the recording
+    ///*play_key* being the key that plays the recorded keys
+    pub fn new(record_key: Key, play_key: Key) -> Self {
+        KeyMacro {
+            record_key,
+            play_key,
+            counts: false,
+            count: String::new(),
+            keys: Vec::new(),
+            status: MacroStatus::default(),
+        }
+    }
+    ///Enables counts, which are disabled by default since they delay every
+    ///digit until the next key press
+    pub fn counts(mut self, counts: bool) -> Self {
+        self.counts = counts;
+        self
+    }
+    ///A handle to the state of the recorder
+    pub fn status(&self) -> MacroStatus {
+        self.status.clone()
+    }
+    ///The keys of the last recorded macro
+    pub fn keys(&self) -> &[Key] {
+        &self.keys
+    }
+    fn key_events<E: Event>(keys: impl Iterator<Item = Key>) -> Vec<E> {
+        keys.map(|k| E::from_system_event(SystemEvent::KeyPress(k)))
+            .collect()
+    }
+}
+
+impl<E: Event> Middleware<E> for KeyMacro {
+    fn intercept(&mut self, e: E) -> Vec<E> {
+        let k = match e.to_system_event() {
+            Some(SystemEvent::KeyPress(k)) => k,
+            _ => return vec![e],
+        };
+        if self.status.is_recording() {
+            if k == self.record_key {
+                self.status.0.store(false, Ordering::SeqCst);
+            } else if k != self.play_key {
+                self.keys.push(k);
+                return vec![e];
+            }
+            return vec![];
+        }
+        if k == self.record_key {
+            self.count.clear();
+            self.keys.clear();
+            self.status.0.store(true, Ordering::SeqCst);
+            return vec![];
+        }
+        if k == self.play_key {
+            let count = match std::mem::take(&mut self.count) {
+                count if count.is_empty() => 1,
+                count => count.parse().unwrap_or(usize::MAX).min(MAX_MACRO_COUNT),
+            };
+            let keys = self.keys.clone();
+            return Self::key_events((0..count).flat_map(|_| keys.iter().copied()));
+        }
+        match k {
+            Key::Char(c)
+                if self.counts && c.is_ascii_digit() && !(c == '0' && self.count.is_empty()) =>
+            {
+                self.count.push(c);
+                vec![]
+            }
+            _ => {
+                let count = std::mem::take(&mut self.count);
+                let mut ret: Vec<E> = Self::key_events(count.chars().map(Key::Char));
+                ret.push(e);
+                ret
+            }
+        }
+    }
+}
+
+///Passes the events in a chain of middleware and returns the events that
+///should reach update
+pub(crate) fn intercept<E: Event>(chain: &mut [Box<dyn Middleware<E>>], e: E) -> Vec<E> {
+    let mut events = vec![e];
+    for m in chain {
+        events = events.into_iter().flat_map(|e| m.intercept(e)).collect();
+    }
+    events
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(c: char) -> SystemEvent {
+        SystemEvent::KeyPress(Key::Char(c))
+    }
+
+    #[test]
+    fn key_macro() {
+        let mut m = KeyMacro::new(Key::Char('Q'), Key::Char('@')).counts(true);
+        let status = m.status();
+        assert!(m.intercept(key('Q')).is_empty());
+        assert!(status.is_recording());
+        assert!(m.intercept(key('a')) == vec![key('a')]);
+        assert!(m.intercept(key('1')) == vec![key('1')]);
+        assert!(m.intercept(key('Q')).is_empty());
+        assert!(!status.is_recording());
+        assert!(m.intercept(key('@')) == vec![key('a'), key('1')]);
+        assert!(m.intercept(key('2')).is_empty());
+        assert!(m.intercept(key('@')) == vec![key('a'), key('1'), key('a'), key('1')]);
+        assert!(m.intercept(key('3')).is_empty());
+        assert!(m.intercept(key('x')) == vec![key('3'), key('x')]);
+        for c in "99999999999999999999999".chars() {
+            assert!(m.intercept(key(c)).is_empty());
+        }
+        assert_eq!(m.intercept(key('@')).len(), MAX_MACRO_COUNT * 2);
+    }
+    #[test]
+    fn chain() {
+        let mut chain: Vec<Box<dyn Middleware<SystemEvent>>> = vec![
+            Box::new(from_fn(|e| match e {
+                SystemEvent::MousePress(..) => vec![],
+                SystemEvent::KeyPress(Key::Char('x')) => {
+                    vec![e.clone(), SystemEvent::KeyPress(Key::Char('q'))]
+                }
+                e => vec![e],
+            })),
+            Box::new(QuitKeys(vec![Key::Char('q')])),
+        ];
+        assert!(intercept(
+            &mut chain,
+            SystemEvent::MousePress(crate::MouseButton::Left, 0, 0)
+        )
+        .is_empty());
+        assert!(
+            intercept(&mut chain, SystemEvent::KeyPress(Key::Char('x')))
+                == vec![SystemEvent::KeyPress(Key::Char('x')), SystemEvent::Quit]
+        );
+        assert!(
+            intercept(&mut chain, SystemEvent::WindowResize(1, 1))
+                == vec![SystemEvent::WindowResize(1, 1)]
+        );
+    }
+}
@@ -0,0 +1,405 @@
+use crate::{Key, MouseButton, Selection, SystemEvent};
+use std::io::{BufRead, Write};
+use std::time::{Duration, Instant};
+
+const HEADER: &str = "rubble_tea recording 1";
+
+///A list of input events and the time at which they were received, relative
+///to the start of the program, along with the system events returned by
+///commands (timer ticks, clipboard requests...). Recordings are created by
+///*Program::record* and played back by *Program::replay*.
+///
+///During a replay the commands returned by update still run but the system
+///events they return are replaced by the recorded ones, so they reach update
+///at the same point of the session as they did when recording. Custom events
+///can not be recorded and are delivered whenever their command returns.
+#[derive(Clone, Default)]
+pub struct Recording {
+    events: Vec<(Duration, SystemEvent)>,
+    results: Vec<(Duration, SystemEvent)>,
+}
+
+//Where a recorded event came from
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub(crate) enum Source {
+    Input,
+    Command,
+}
+
+impl Recording {
+    ///Creates a recording out of the given input events, the events should be
+    ///sorted by time
+    pub fn new(events: Vec<(Duration, SystemEvent)>) -> Self {
+        Recording {
+            events,
+            results: Vec::new(),
+        }
+    }
+    ///Adds the system events returned by commands and the time at which they
+    ///were returned, the events should be sorted by time
+    pub fn with_results(mut self, results: Vec<(Duration, SystemEvent)>) -> Self {
+        self.results = results;
+        self
+    }
+    ///Loads a recording from the file at *path*
+    pub fn load<P: AsRef<std::path::Path>>(path: P) -> std::io::Result<Self> {
+        Recording::read(std::io::BufReader::new(std::fs::File::open(path)?))
+    }
+    ///Reads a recording that was written by *Program::record* or *Recording::save*
+    pub fn read<R: BufRead>(reader: R) -> std::io::Result<Self> {
+        let invalid = |line: &str| {
+            std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                format!("invalid recording line: {:?}", line),
+            )
+        };
+        let mut lines = reader.lines();
+        match lines.next() {
+            Some(Ok(header)) if header == HEADER => (),
+            Some(Err(e)) => return Err(e),
+            _ => return Err(invalid("missing header")),
+        }
+        let mut rec = Recording::default();
+        for line in lines {
+            let line = line?;
+            if line.is_empty() {
+                continue;
+            }
+            match parse_line(&line).ok_or_else(|| invalid(&line))? {
+                (time, Source::Input, e) => rec.events.push((time, e)),
+                (time, Source::Command, e) => rec.results.push((time, e)),
+            }
+        }
+        Ok(rec)
+    }
+    ///Saves the recording to the file at *path*
+    pub fn save<P: AsRef<std::path::Path>>(&self, path: P) -> std::io::Result<()> {
+        let mut file = std::io::BufWriter::new(std::fs::File::create(path)?);
+        writeln!(file, "{}", HEADER)?;
+        for (time, source, e) in self.timeline() {
+            if let Some(line) = format_line(time, source, e) {
+                writeln!(file, "{}", line)?;
+            }
+        }
+        file.flush()
+    }
+    ///The recorded events and the time at which they were received
+    pub fn events(&self) -> &[(Duration, SystemEvent)] {
+        &self.events
+    }
+    ///The system events returned by commands and the time at which they were
+    ///returned
+    pub fn results(&self) -> &[(Duration, SystemEvent)] {
+        &self.results
+    }
+    //The input events and the command results merged by time, input comes
+    //first when both happened at the same time
+    pub(crate) fn timeline(&self) -> Vec<(Duration, Source, &SystemEvent)> {
+        let mut timeline: Vec<_> = self
+            .events
+            .iter()
+            .map(|(t, e)| (*t, Source::Input, e))
+            .chain(self.results.iter().map(|(t, e)| (*t, Source::Command, e)))
+            .collect();
+        timeline.sort_by_key(|(t, _, _)| *t);
+        timeline
+    }
+}
+
+///Appends input events and command results to a recording file as they are
+///received
+pub(crate) struct Recorder {
+    file: std::io::LineWriter<std::fs::File>,
+    start: Instant,
+}
+
+impl Recorder {
+    pub(crate) fn create<P: AsRef<std::path::Path>>(path: P) -> std::io::Result<Self> {
+        let mut file = std::io::LineWriter::new(std::fs::File::create(path)?);
+        writeln!(file, "{}", HEADER)?;
+        Ok(Recorder {
+            file,
+            start: Instant::now(),
+        })
+    }
+    pub(crate) fn record(&mut self, source: Source, e: &SystemEvent) {
+        if let Some(line) = format_line(self.start.elapsed(), source, e) {
+            //A broken recording should not break the program
+            let _ = writeln!(self.file, "{}", line);
+        }
+    }
+}
+
+fn format_key(k: &Key) -> Option<String> {
+    Some(match k {
+        Key::Backspace => "backspace".to_string(),
+        Key::Left => "left".to_string(),
+        Key::Right => "right".to_string(),
+        Key::Up => "up".to_string(),
+        Key::Down => "down".to_string(),
+        Key::Home => "home".to_string(),
+        Key::End => "end".to_string(),
+        Key::PageUp => "pageup".to_string(),
+        Key::PageDown => "pagedown".to_string(),
+        Key::BackTab => "backtab".to_string(),
+        Key::Delete => "delete".to_string(),
+        Key::Insert => "insert".to_string(),
+        Key::F(n) => format!("f {}", n),
+        Key::Char(c) => format!("char {}", *c as u32),
+        Key::Alt(c) => format!("alt {}", *c as u32),
+        Key::Ctrl(c) => format!("ctrl {}", *c as u32),
+        Key::Null => "null".to_string(),
+        Key::Esc => "esc".to_string(),
+        //Recording an unknown key as another key would corrupt the replay
+        _ => return None,
+    })
+}
+
+fn parse_key(fields: &[&str]) -> Option<Key> {
+    let chr = || char::from_u32(fields.get(1)?.parse().ok()?);
+    Some(match *fields.first()? {
+        "backspace" => Key::Backspace,
+        "left" => Key::Left,
+        "right" => Key::Right,
+        "up" => Key::Up,
+        "down" => Key::Down,
+        "home" => Key::Home,
+        "end" => Key::End,
+        "pageup" => Key::PageUp,
+        "pagedown" => Key::PageDown,
+        "backtab" => Key::BackTab,
+        "delete" => Key::Delete,
+        "insert" => Key::Insert,
+        "f" => Key::F(fields.get(1)?.parse().ok()?),
+        "char" => Key::Char(chr()?),
+        "alt" => Key::Alt(chr()?),
+        "ctrl" => Key::Ctrl(chr()?),
+        "null" => Key::Null,
+        "esc" => Key::Esc,
+        _ => return None,
+    })
+}
+
+fn format_button(b: &MouseButton) -> &'static str {
+    match b {
+        MouseButton::Left => "left",
+        MouseButton::Right => "right",
+        MouseButton::Middle => "middle",
+        MouseButton::WheelUp => "wheelup",
+        MouseButton::WheelDown => "wheeldown",
+    }
+}
+
+fn parse_button(s: &str) -> Option<MouseButton> {
+    Some(match s {
+        "left" => MouseButton::Left,
+        "right" => MouseButton::Right,
+        "middle" => MouseButton::Middle,
+        "wheelup" => MouseButton::WheelUp,
+        "wheeldown" => MouseButton::WheelDown,
+        _ => return None,
+    })
+}
+
+fn format_selection(s: &Selection) -> char {
+    match s {
+        Selection::Clipboard => 'c',
+        Selection::Primary => 'p',
+    }
+}
+
+fn parse_selection(s: &str) -> Option<Selection> {
+    match s {
+        "c" => Some(Selection::Clipboard),
+        "p" => Some(Selection::Primary),
+        _ => None,
+    }
+}
+
+//Whether the event can be written to a recording
+pub(crate) fn recordable(e: &SystemEvent) -> bool {
+    format_event(e).is_some()
+}
+
+fn format_event(e: &SystemEvent) -> Option<String> {
+    Some(match e {
+        SystemEvent::KeyPress(k) => format!("key {}", format_key(k)?),
+        SystemEvent::MousePress(b, x, y) => format!("press {} {} {}", format_button(b), x, y),
+        SystemEvent::MouseRelease(x, y) => format!("release {} {}", x, y),
+        SystemEvent::MouseHold(x, y) => format!("hold {} {}", x, y),
+        SystemEvent::WindowResize(w, h) => format!("resize {} {}", w, h),
+        SystemEvent::SetClipboard(s, text) => format!(
+            "setclipboard {} {}",
+            format_selection(s),
+            crate::clipboard::base64_encode(text.as_bytes())
+        ),
+        SystemEvent::RequestClipboard(s) => format!("requestclipboard {}", format_selection(s)),
+        SystemEvent::Clipboard(s, text) => format!(
+            "clipboard {} {}",
+            format_selection(s),
+            crate::clipboard::base64_encode(text.as_bytes())
+        ),
+        SystemEvent::Tick(id, generation) => format!("tick {} {}", id, generation),
+        SystemEvent::Interrupt => "interrupt".to_string(),
+        SystemEvent::Terminate => "terminate".to_string(),
+        SystemEvent::Hangup => "hangup".to_string(),
+        SystemEvent::QuitSignal => "sigquit".to_string(),
+        SystemEvent::Quit => "quit".to_string(),
+    })
+}
+
+//Command results are marked with `cmd` after the time
+fn format_line(time: Duration, source: Source, e: &SystemEvent) -> Option<String> {
+    let event = format_event(e)?;
+    Some(match source {
+        Source::Input => format!("{} {}", time.as_micros(), event),
+        Source::Command => format!("{} cmd {}", time.as_micros(), event),
+    })
+}
+
+fn parse_line(line: &str) -> Option<(Duration, Source, SystemEvent)> {
+    let mut fields: Vec<&str> = line.split(' ').collect();
+    let time = Duration::from_micros(fields.first()?.parse().ok()?);
+    let source = if fields.get(1) == Some(&"cmd") {
+        fields.remove(1);
+        Source::Command
+    } else {
+        Source::Input
+    };
+    let num = |i: usize| -> Option<u16> { fields.get(i)?.parse().ok() };
+    let event = match *fields.get(1)? {
+        "key" => SystemEvent::KeyPress(parse_key(&fields[2..])?),
+        "press" => SystemEvent::MousePress(parse_button(fields.get(2)?)?, num(3)?, num(4)?),
+        "release" => SystemEvent::MouseRelease(num(2)?, num(3)?),
+        "hold" => SystemEvent::MouseHold(num(2)?, num(3)?),
+        "resize" => SystemEvent::WindowResize(num(2)?, num(3)?),
+        "setclipboard" => SystemEvent::SetClipboard(
+            parse_selection(fields.get(2)?)?,
+            String::from_utf8(crate::clipboard::base64_decode(fields.get(3)?)?).ok()?,
+        ),
+        "requestclipboard" => SystemEvent::RequestClipboard(parse_selection(fields.get(2)?)?),
+        "clipboard" => SystemEvent::Clipboard(
+            parse_selection(fields.get(2)?)?,
+            String::from_utf8(crate::clipboard::base64_decode(fields.get(3)?)?).ok()?,
+        ),
+        "tick" => SystemEvent::Tick(fields.get(2)?.parse().ok()?, fields.get(3)?.parse().ok()?),
+        "interrupt" => SystemEvent::Interrupt,
+        "terminate" => SystemEvent::Terminate,
+        "hangup" => SystemEvent::Hangup,
+        "sigquit" => SystemEvent::QuitSignal,
+        "quit" => SystemEvent::Quit,
+        _ => return None,
+    };
+    Some((time, source, event))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn line_round_trip() {
+        let events = vec![
+            SystemEvent::KeyPress(Key::Char(' ')),
+            SystemEvent::KeyPress(Key::Char('\n')),
+            SystemEvent::KeyPress(Key::Ctrl('c')),
+            SystemEvent::KeyPress(Key::F(5)),
+            SystemEvent::KeyPress(Key::Esc),
+            SystemEvent::MousePress(MouseButton::WheelUp, 3, 4),
+            SystemEvent::MouseRelease(1, 2),
+            SystemEvent::WindowResize(80, 24),
+            SystemEvent::Clipboard(Selection::Primary, "two words".to_string()),
+            SystemEvent::Terminate,
+        ];
+        for (i, e) in events.into_iter().enumerate() {
+            let time = Duration::from_millis(i as u64 * 10);
+            let line = format_line(time, Source::Input, &e).unwrap();
+            assert!(
+                parse_line(&line) == Some((time, Source::Input, e)),
+                "{}",
+                line
+            );
+        }
+    }
+    #[test]
+    fn result_line_round_trip() {
+        let events = vec![
+            SystemEvent::Tick(7, 2),
+            SystemEvent::RequestClipboard(Selection::Clipboard),
+            SystemEvent::SetClipboard(Selection::Primary, "copied text".to_string()),
+        ];
+        for e in events {
+            let time = Duration::from_millis(3);
+            let line = format_line(time, Source::Command, &e).unwrap();
+            assert!(line.starts_with("3000 cmd "), "{}", line);
+            assert!(
+                parse_line(&line) == Some((time, Source::Command, e)),
+                "{}",
+                line
+            );
+        }
+    }
+    #[test]
+    fn replay_headless() {
+        use crate::tests::{keys, Counter};
+        let frames = crate::Program::new()
+            .replay(keys("++"))
+            .run_headless(&mut Counter(0), vec![]);
+        assert_eq!(frames, ["0", "1", "2"]);
+    }
+    #[test]
+    fn replay_results() {
+        use crate::tests::keys;
+        //Shows the generation of the last tick, '+' starts a command
+        struct Ticker(u64);
+        impl crate::Model<SystemEvent> for Ticker {
+            fn update(
+                &mut self,
+                e: &SystemEvent,
+            ) -> Vec<Box<dyn FnOnce() -> SystemEvent + Send + 'static>> {
+                match e {
+                    SystemEvent::KeyPress(Key::Char('+')) => {
+                        vec![Box::new(|| SystemEvent::Tick(0, 7))]
+                    }
+                    SystemEvent::Tick(_, g) => {
+                        self.0 = *g;
+                        vec![]
+                    }
+                    _ => vec![],
+                }
+            }
+            fn view(&self) -> String {
+                self.0.to_string()
+            }
+        }
+        let recording =
+            keys("+").with_results(vec![(Duration::from_millis(5), SystemEvent::Tick(0, 5))]);
+        let frames = crate::Program::new()
+            .replay(recording)
+            .run_headless(&mut Ticker(0), vec![]);
+        assert_eq!(frames, ["0", "5"]);
+    }
+    #[test]
+    fn read_recording() {
+        let text = format!("{}\n0 resize 80 24\n1500 key char 113\n", HEADER);
+        let rec = Recording::read(text.as_bytes()).unwrap();
+        assert!(
+            rec.events()
+                == [
+                    (Duration::ZERO, SystemEvent::WindowResize(80, 24)),
+                    (
+                        Duration::from_micros(1500),
+                        SystemEvent::KeyPress(Key::Char('q'))
+                    ),
+                ]
+        );
+        assert!(Recording::read("0 resize 80 24\n".as_bytes()).is_err());
+    }
+    #[test]
+    fn read_results() {
+        let text = format!("{}\n0 resize 80 24\n900 cmd tick 1 0\n", HEADER);
+        let rec = Recording::read(text.as_bytes()).unwrap();
+        assert!(rec.events() == [(Duration::ZERO, SystemEvent::WindowResize(80, 24))]);
+        assert!(rec.results() == [(Duration::from_micros(900), SystemEvent::Tick(1, 0))]);
+    }
+}
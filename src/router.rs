@@ -0,0 +1,260 @@
+use crate::{Event, Model, SystemEvent};
+use std::collections::HashMap;
+
+type Factory<E> = Box<dyn Fn() -> Box<dyn Screen<E>>>;
+type Unwrap<E> = Box<dyn Fn(&E) -> Option<Navigation>>;
+
+///A model that can be put in a *Router*. The hooks are called when the
+///screen becomes or stops being the visible one.
+pub trait Screen<E: Event>: Model<E> {
+    ///Called when the screen is pushed or uncovered by a pop
+    fn on_enter(&mut self) -> Vec<Box<dyn FnOnce() -> E + Send + 'static>> {
+        vec![]
+    }
+    ///Called when the screen is popped, replaced or covered by a push
+    fn on_leave(&mut self) -> Vec<Box<dyn FnOnce() -> E + Send + 'static>> {
+        vec![]
+    }
+}
+
+///The navigation requests understood by a *Router*. The routes are the names
+///registered with *Router::route*.
+#[derive(Eq, PartialEq, Clone, Debug)]
+pub enum Navigation {
+    ///Push the screen of the given route on top of the stack
+    Push(String),
+    ///Remove the top screen, the last screen is never removed
+    Pop,
+    ///Replace the top screen with the screen of the given route
+    Replace(String),
+}
+
+///A stack of screens. Events are forwarded to the top screen, which is the
+///only one that gets rendered, except for *SystemEvent::WindowResize* which is
+///forwarded to every screen so that covered screens know the current size and
+///*SystemEvent::Tick* which is forwarded to every screen so that the widgets
+///of covered screens keep ticking.
+pub struct Router<E: Event> {
+    stack: Vec<Box<dyn Screen<E>>>,
+    routes: HashMap<String, Factory<E>>,
+    unwrap: Unwrap<E>,
+    size: Option<(u16, u16)>,
+}
+
+impl<E: Event> Router<E> {
+    ///Creates a new router with
+    ///*root* being the first screen, which can never be popped
+    ///*unwrap* turning the events that request navigation into a *Navigation*
+    pub fn new<U: Fn(&E) -> Option<Navigation> + 'static>(
+        root: Box<dyn Screen<E>>,
+        unwrap: U,
+    ) -> Self {
+        Router {
+            stack: vec![root],
+            routes: HashMap::new(),
+            unwrap: Box::new(unwrap),
+            size: None,
+        }
+    }
+    ///Registers a route that can be navigated to by name, *factory* creates
+    ///a new screen every time the route is pushed
+    pub fn route<S: Into<String>, F: Fn() -> Box<dyn Screen<E>> + 'static>(
+        mut self,
+        name: S,
+        factory: F,
+    ) -> Self {
+        self.routes.insert(name.into(), Box::new(factory));
+        self
+    }
+    ///The number of screens on the stack
+    pub fn depth(&self) -> usize {
+        self.stack.len()
+    }
+    //The new screen did not see the resize events sent before it was created
+    fn enter(&mut self) -> Vec<Box<dyn FnOnce() -> E + Send + 'static>> {
+        let top = self.stack.last_mut().unwrap();
+        let mut cmds = top.on_enter();
+        if let Some((w, h)) = self.size {
+            cmds.extend(top.update(&E::from_system_event(SystemEvent::WindowResize(w, h))));
+        }
+        cmds
+    }
+    ///Pushes a screen on top of the stack and returns the commands returned by
+    ///the hooks
+    pub fn push(
+        &mut self,
+        screen: Box<dyn Screen<E>>,
+    ) -> Vec<Box<dyn FnOnce() -> E + Send + 'static>> {
+        let mut cmds = self.stack.last_mut().unwrap().on_leave();
+        self.stack.push(screen);
+        cmds.extend(self.enter());
+        cmds
+    }
+    ///Removes the top screen unless it is the last one and returns the
+    ///commands returned by the hooks
+    pub fn pop(&mut self) -> Vec<Box<dyn FnOnce() -> E + Send + 'static>> {
+        if self.stack.len() < 2 {
+            return vec![];
+        }
+        let mut cmds = self.stack.pop().unwrap().on_leave();
+        cmds.extend(self.stack.last_mut().unwrap().on_enter());
+        cmds
+    }
+    ///Replaces the top screen and returns the commands returned by the hooks
+    pub fn replace(
+        &mut self,
+        screen: Box<dyn Screen<E>>,
+    ) -> Vec<Box<dyn FnOnce() -> E + Send + 'static>> {
+        let mut cmds = self.stack.last_mut().unwrap().on_leave();
+        *self.stack.last_mut().unwrap() = screen;
+        cmds.extend(self.enter());
+        cmds
+    }
+    fn navigate(&mut self, nav: Navigation) -> Vec<Box<dyn FnOnce() -> E + Send + 'static>> {
+        match nav {
+            Navigation::Pop => self.pop(),
+            Navigation::Push(route) | Navigation::Replace(route)
+                if !self.routes.contains_key(&route) =>
+            {
+                crate::log!("no screen is registered for the route {:?}", route);
+                vec![]
+            }
+            Navigation::Push(route) => {
+                let screen = self.routes[&route]();
+                self.push(screen)
+            }
+            Navigation::Replace(route) => {
+                let screen = self.routes[&route]();
+                self.replace(screen)
+            }
+        }
+    }
+}
+
+impl<E: Event> Model<E> for Router<E> {
+    fn update(&mut self, e: &E) -> Vec<Box<dyn FnOnce() -> E + Send + 'static>> {
+        if let Some(nav) = (self.unwrap)(e) {
+            return self.navigate(nav);
+        }
+        match e.to_system_event() {
+            Some(SystemEvent::WindowResize(w, h)) => {
+                self.size = Some((w, h));
+                self.stack.iter_mut().flat_map(|s| s.update(e)).collect()
+            }
+            //A widget only schedules its next tick when it receives the
+            //current one, so a dropped tick would stop it for good
+            Some(SystemEvent::Tick(..)) => {
+                self.stack.iter_mut().flat_map(|s| s.update(e)).collect()
+            }
+            _ => self.stack.last_mut().unwrap().update(e),
+        }
+    }
+    fn view(&self) -> String {
+        self.stack.last().unwrap().view()
+    }
+    fn rich_view(&self) -> crate::view::View {
+        self.stack.last().unwrap().rich_view()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Key;
+    use std::sync::{Arc, Mutex};
+
+    #[derive(Eq, PartialEq, Clone)]
+    enum Ev {
+        Se(SystemEvent),
+        Nav(Navigation),
+    }
+    impl Event for Ev {
+        fn from_system_event(se: SystemEvent) -> Self {
+            Ev::Se(se)
+        }
+        fn to_system_event(&self) -> Option<SystemEvent> {
+            match self {
+                Ev::Se(se) => Some(se.clone()),
+                _ => None,
+            }
+        }
+    }
+
+    struct Page {
+        name: &'static str,
+        events: usize,
+        hooks: Arc<Mutex<Vec<String>>>,
+    }
+    impl Model<Ev> for Page {
+        fn update(&mut self, e: &Ev) -> Vec<Box<dyn FnOnce() -> Ev + Send + 'static>> {
+            if let Ev::Se(SystemEvent::KeyPress(_) | SystemEvent::Tick(..)) = e {
+                self.events += 1;
+            }
+            vec![]
+        }
+        fn view(&self) -> String {
+            format!("{} {}", self.name, self.events)
+        }
+    }
+    impl Screen<Ev> for Page {
+        fn on_enter(&mut self) -> Vec<Box<dyn FnOnce() -> Ev + Send + 'static>> {
+            self.hooks
+                .lock()
+                .unwrap()
+                .push(format!("enter {}", self.name));
+            vec![]
+        }
+        fn on_leave(&mut self) -> Vec<Box<dyn FnOnce() -> Ev + Send + 'static>> {
+            self.hooks
+                .lock()
+                .unwrap()
+                .push(format!("leave {}", self.name));
+            vec![]
+        }
+    }
+
+    fn router(hooks: &Arc<Mutex<Vec<String>>>) -> Router<Ev> {
+        let page = |name, hooks: &Arc<Mutex<Vec<String>>>| {
+            Box::new(Page {
+                name,
+                events: 0,
+                hooks: hooks.clone(),
+            })
+        };
+        let detail_hooks = hooks.clone();
+        Router::new(page("list", hooks), |e| match e {
+            Ev::Nav(n) => Some(n.clone()),
+            _ => None,
+        })
+        .route("detail", move || page("detail", &detail_hooks))
+    }
+
+    #[test]
+    fn navigation() {
+        let hooks = Arc::new(Mutex::new(Vec::new()));
+        let mut router = router(&hooks);
+        let key = Ev::Se(SystemEvent::KeyPress(Key::Char('a')));
+        router.update(&key);
+        assert_eq!(router.view(), "list 1");
+        router.update(&Ev::Nav(Navigation::Push("detail".to_string())));
+        router.update(&key);
+        assert_eq!(router.view(), "detail 1");
+        router.update(&Ev::Nav(Navigation::Pop));
+        assert_eq!(router.view(), "list 1");
+        router.update(&Ev::Nav(Navigation::Pop));
+        assert_eq!(router.depth(), 1);
+        assert_eq!(
+            *hooks.lock().unwrap(),
+            ["leave list", "enter detail", "leave detail", "enter list"]
+        );
+    }
+    #[test]
+    fn covered_screens_tick() {
+        let mut router = router(&Arc::new(Mutex::new(Vec::new())));
+        router.update(&Ev::Nav(Navigation::Push("detail".to_string())));
+        router.update(&Ev::Se(SystemEvent::Tick(1, 0)));
+        assert_eq!(router.view(), "detail 1");
+        router.update(&Ev::Nav(Navigation::Pop));
+        assert_eq!(router.view(), "list 1");
+    }
+}
@@ -0,0 +1,256 @@
+use crate::keymap::{key_name, parse_key};
+use crate::middleware::Middleware;
+use crate::{Event, Key, SystemEvent};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+///Parses a space separated sequence of key names such as `g g` or
+///`ctrl+x ctrl+s`
+pub fn parse_sequence(s: &str) -> Option<Vec<Key>> {
+    s.split_whitespace().map(parse_key).collect()
+}
+
+///A handle to the keys typed so far of an unfinished sequence, meant to be
+///kept by the model so that it can display them
+#[derive(Clone, Default)]
+pub struct PendingKeys(Arc<Mutex<Vec<Key>>>);
+
+impl PendingKeys {
+    ///The keys typed so far
+    pub fn keys(&self) -> Vec<Key> {
+        self.0.lock().unwrap().clone()
+    }
+    ///The names of the keys typed so far separated by spaces, empty if no
+    ///sequence is in progress
+    pub fn view(&self) -> String {
+        self.0
+            .lock()
+            .unwrap()
+            .iter()
+            .map(key_name)
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+}
+
+///The result of feeding a key to *Sequences*
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub enum Feed<E> {
+    ///The key is part of a sequence that has not been completed yet
+    Pending,
+    ///Events that should be handled in place of the fed key, either the
+    ///action of a completed sequence or key presses that did not form one
+    Events(Vec<E>),
+}
+
+///Matches sequences of key presses and turns each completed sequence into a
+///single action event. Keys are buffered while they form the beginning of a
+///sequence; if no key arrives within the timeout the buffered keys are
+///either turned into the action of the sequence they complete (when a
+///sequence is a prefix of a longer one) or passed on as they are.
+///
+///*Sequences* is a *Middleware* so it can be added with
+///*Program::middleware*, models can also call *feed* themselves.
+pub struct Sequences<E: Event> {
+    sequences: Vec<(Vec<Key>, E)>,
+    timeout: Duration,
+    timeout_event: E,
+    pending: PendingKeys,
+    last_key: Instant,
+    cmds: Vec<Box<dyn FnOnce() -> E + Send + 'static>>,
+}
+
+impl<E: Event> Sequences<E> {
+    ///Creates a new matcher with
+    ///*timeout* being how long to wait for the next key of a sequence
+    ///*timeout_event* the event that is sent to the matcher when the timeout
+    ///might have expired, it never reaches update
+    pub fn new(timeout: Duration, timeout_event: E) -> Self {
+        Sequences {
+            sequences: Vec::new(),
+            timeout,
+            timeout_event,
+            pending: PendingKeys::default(),
+            last_key: Instant::now(),
+            cmds: Vec::new(),
+        }
+    }
+    ///Adds a sequence that emmits *action* when completed
+    pub fn bind(mut self, keys: Vec<Key>, action: E) -> Self {
+        self.sequences.push((keys, action));
+        self
+    }
+    ///A handle to the keys of the sequence in progress
+    pub fn pending(&self) -> PendingKeys {
+        self.pending.clone()
+    }
+    fn key_events(keys: &[Key]) -> Vec<E> {
+        keys.iter()
+            .map(|k| E::from_system_event(SystemEvent::KeyPress(*k)))
+            .collect()
+    }
+    //The action of the sequence made of exactly *keys*, or the keys themselves
+    fn flush(&self, keys: &[Key]) -> Vec<E> {
+        match self.sequences.iter().find(|(s, _)| s == keys) {
+            Some((_, action)) => vec![action.clone()],
+            None => Self::key_events(keys),
+        }
+    }
+    //Empties the buffer after the timeout expired
+    fn resolve(&mut self) -> Vec<E> {
+        let keys = std::mem::take(&mut *self.pending.0.lock().unwrap());
+        self.flush(&keys)
+    }
+    ///Handles a key press
+    pub fn feed(&mut self, k: Key) -> Feed<E> {
+        let mut ret = Vec::new();
+        if !self.pending.0.lock().unwrap().is_empty() && self.last_key.elapsed() >= self.timeout {
+            ret = self.resolve();
+        }
+        self.last_key = Instant::now();
+        let mut pending = self.pending.0.lock().unwrap();
+        pending.push(k);
+        let longer = self
+            .sequences
+            .iter()
+            .any(|(s, _)| s.len() > pending.len() && s.starts_with(&pending));
+        if longer {
+            drop(pending);
+            let e = self.timeout_event.clone();
+            let timeout = self.timeout;
+            self.cmds.push(Box::new(move || {
+                std::thread::sleep(timeout);
+                e
+            }));
+            return if ret.is_empty() {
+                Feed::Pending
+            } else {
+                Feed::Events(ret)
+            };
+        }
+        let keys = std::mem::take(&mut *pending);
+        drop(pending);
+        if let Some((_, action)) = self.sequences.iter().find(|(s, _)| *s == keys) {
+            ret.push(action.clone());
+        } else if keys.len() == 1 {
+            ret.extend(Self::key_events(&keys));
+        } else {
+            //The buffered keys might be a sequence of their own and the last
+            //key might start a new one
+            ret.extend(self.flush(&keys[..keys.len() - 1]));
+            match self.feed(keys[keys.len() - 1]) {
+                Feed::Events(e) => ret.extend(e),
+                Feed::Pending => (),
+            }
+        }
+        Feed::Events(ret)
+    }
+}
+
+impl<E: Event> Middleware<E> for Sequences<E> {
+    fn intercept(&mut self, e: E) -> Vec<E> {
+        if e == self.timeout_event {
+            if !self.pending.0.lock().unwrap().is_empty() && self.last_key.elapsed() >= self.timeout
+            {
+                return self.resolve();
+            }
+            return vec![];
+        }
+        match e.to_system_event() {
+            Some(SystemEvent::KeyPress(k)) => match self.feed(k) {
+                Feed::Pending => vec![],
+                Feed::Events(e) => e,
+            },
+            _ => vec![e],
+        }
+    }
+    fn take_commands(&mut self) -> Vec<Box<dyn FnOnce() -> E + Send + 'static>> {
+        std::mem::take(&mut self.cmds)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Eq, PartialEq, Clone, Debug)]
+    enum Ev {
+        Se(SystemEvent),
+        Top,
+        Top2,
+        Save,
+        Timeout,
+    }
+    impl Event for Ev {
+        fn from_system_event(se: SystemEvent) -> Self {
+            Ev::Se(se)
+        }
+        fn to_system_event(&self) -> Option<SystemEvent> {
+            match self {
+                Ev::Se(se) => Some(se.clone()),
+                _ => None,
+            }
+        }
+    }
+    fn key(c: char) -> Ev {
+        Ev::Se(SystemEvent::KeyPress(Key::Char(c)))
+    }
+    fn sequences(timeout: Duration) -> Sequences<Ev> {
+        Sequences::new(timeout, Ev::Timeout)
+            .bind(parse_sequence("g g").unwrap(), Ev::Top)
+            .bind(parse_sequence("ctrl+x ctrl+s").unwrap(), Ev::Save)
+    }
+
+    #[test]
+    fn complete_sequence() {
+        let mut s = sequences(Duration::from_secs(60));
+        assert!(s.intercept(key('g')).is_empty());
+        assert_eq!(s.pending().view(), "g");
+        assert_eq!(s.take_commands().len(), 1);
+        assert_eq!(s.intercept(key('g')), vec![Ev::Top]);
+        assert_eq!(s.pending().view(), "");
+        assert!(s
+            .intercept(Ev::Se(SystemEvent::KeyPress(Key::Ctrl('x'))))
+            .is_empty());
+        assert_eq!(
+            s.intercept(Ev::Se(SystemEvent::KeyPress(Key::Ctrl('s')))),
+            vec![Ev::Save]
+        );
+    }
+    #[test]
+    fn broken_sequence() {
+        let mut s = sequences(Duration::from_secs(60));
+        assert_eq!(s.intercept(key('a')), vec![key('a')]);
+        s.intercept(key('g'));
+        assert_eq!(s.intercept(key('x')), vec![key('g'), key('x')]);
+        s.intercept(key('g'));
+        assert_eq!(
+            s.intercept(Ev::Se(SystemEvent::KeyPress(Key::Ctrl('x')))),
+            vec![key('g')]
+        );
+        assert_eq!(s.pending().view(), "ctrl+x");
+    }
+    #[test]
+    fn broken_sequence_with_bound_prefix() {
+        let mut s = sequences(Duration::from_secs(60)).bind(vec![Key::Char('g')], Ev::Top2);
+        assert!(s.intercept(key('g')).is_empty());
+        assert_eq!(s.intercept(key('x')), vec![Ev::Top2, key('x')]);
+        s.intercept(key('g'));
+        assert_eq!(
+            s.intercept(Ev::Se(SystemEvent::KeyPress(Key::Ctrl('x')))),
+            vec![Ev::Top2]
+        );
+        assert_eq!(s.pending().view(), "ctrl+x");
+    }
+    #[test]
+    fn timeout() {
+        let mut s = sequences(Duration::ZERO).bind(vec![Key::Char('g')], Ev::Top2);
+        assert!(s.intercept(key('g')).is_empty());
+        assert_eq!(s.intercept(Ev::Timeout), vec![Ev::Top2]);
+        s.intercept(Ev::Se(SystemEvent::KeyPress(Key::Ctrl('x'))));
+        assert_eq!(
+            s.intercept(key('q')),
+            vec![Ev::Se(SystemEvent::KeyPress(Key::Ctrl('x'))), key('q')]
+        );
+    }
+}
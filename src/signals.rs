@@ -0,0 +1,108 @@
+use crate::SystemEvent;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+//One flag per handled signal, set from the signal handler and cleared by the
+//thread that turns them into events. Setting an atomic is one of the few
+//things that is safe to do inside a signal handler.
+static RECEIVED: [AtomicBool; 4] = [
+    AtomicBool::new(false),
+    AtomicBool::new(false),
+    AtomicBool::new(false),
+    AtomicBool::new(false),
+];
+const SIGNALS: [libc::c_int; 4] = [libc::SIGINT, libc::SIGTERM, libc::SIGHUP, libc::SIGQUIT];
+
+extern "C" fn on_signal(sig: libc::c_int) {
+    if let Some(i) = SIGNALS.iter().position(|x| *x == sig) {
+        RECEIVED[i].store(true, Ordering::SeqCst);
+    }
+}
+
+fn to_system_event(sig: libc::c_int) -> SystemEvent {
+    match sig {
+        libc::SIGINT => SystemEvent::Interrupt,
+        libc::SIGTERM => SystemEvent::Terminate,
+        libc::SIGHUP => SystemEvent::Hangup,
+        _ => SystemEvent::QuitSignal,
+    }
+}
+
+///Keeps the signal handlers installed, dropping it puts the previous handlers
+///back and stops the thread that turns signals into events
+pub(crate) struct Watcher {
+    previous: Vec<libc::sighandler_t>,
+    stop: Arc<AtomicBool>,
+    thread: Option<std::thread::JoinHandle<()>>,
+}
+
+///Installs the signal handlers before returning and calls *f* on a new thread
+///with the event of every signal received until the watcher is dropped
+pub(crate) fn watch<F: FnMut(SystemEvent) + Send + 'static>(mut f: F) -> Watcher {
+    for flag in &RECEIVED {
+        flag.store(false, Ordering::SeqCst);
+    }
+    let previous = SIGNALS
+        .iter()
+        .map(|sig| unsafe {
+            libc::signal(
+                *sig,
+                on_signal as extern "C" fn(libc::c_int) as libc::sighandler_t,
+            )
+        })
+        .collect();
+    let stop = Arc::new(AtomicBool::new(false));
+    let thread = {
+        let stop = stop.clone();
+        std::thread::spawn(move || {
+            while !stop.load(Ordering::SeqCst) {
+                std::thread::sleep(std::time::Duration::new(0, 50000000));
+                for (i, sig) in SIGNALS.iter().enumerate() {
+                    if RECEIVED[i].swap(false, Ordering::SeqCst) {
+                        f(to_system_event(*sig));
+                    }
+                }
+            }
+        })
+    };
+    Watcher {
+        previous,
+        stop,
+        thread: Some(thread),
+    }
+}
+
+impl Drop for Watcher {
+    fn drop(&mut self) {
+        for (sig, handler) in SIGNALS.iter().zip(&self.previous) {
+            if *handler != libc::SIG_ERR {
+                unsafe {
+                    libc::signal(*sig, *handler);
+                }
+            }
+        }
+        self.stop.store(true, Ordering::SeqCst);
+        if let Some(thread) = self.thread.take() {
+            _ = thread.join();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn handlers_are_restored() {
+        let (tx, rx) = std::sync::mpsc::channel();
+        let watcher = watch(move |e| _ = tx.send(e));
+        unsafe {
+            libc::raise(libc::SIGHUP);
+        }
+        let received = rx.recv_timeout(std::time::Duration::from_secs(1));
+        assert!(received == Ok(SystemEvent::Hangup));
+        drop(watcher);
+        let current = unsafe { libc::signal(libc::SIGHUP, libc::SIG_DFL) };
+        assert_eq!(current, libc::SIG_DFL);
+    }
+}
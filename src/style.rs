@@ -1,4 +1,4 @@
-#[derive(Eq, PartialEq, Clone)]
+#[derive(Eq, PartialEq, Clone, Debug)]
 /// They way the text should be displayed
 pub enum TextStyle {
     Bold,
@@ -10,7 +10,7 @@ pub enum TextStyle {
     StrikeThrough,
 }
 
-#[derive(Eq, PartialEq, Clone)]
+#[derive(Eq, PartialEq, Clone, Debug)]
 ///The color to be printed
 pub enum Color {
     ///The terminal's default black color
@@ -47,7 +47,7 @@ pub enum Color {
     LightWhite,
 }
 
-#[derive(Eq, PartialEq, Clone)]
+#[derive(Eq, PartialEq, Clone, Debug)]
 ///The position of the text
 pub enum Position {
     ///Zero indexed position
@@ -64,7 +64,7 @@ pub enum Position {
     BottomRight,
 }
 
-#[derive(Eq, PartialEq, Clone)]
+#[derive(Eq, PartialEq, Clone, Debug)]
 ///Combination of all styles structures
 pub enum Style {
     /// The style of the text
@@ -209,6 +209,171 @@ impl Default for StyleSheet {
     }
 }
 
+//A piece of styled text, either an escape sequence or a printable character
+pub(crate) enum Token<'a> {
+    Escape(&'a str),
+    Char(char),
+}
+
+//Splits a string into escape sequences (CSI, OSC and two byte escapes) and
+//the characters between them
+pub(crate) fn tokens(s: &str) -> impl Iterator<Item = Token<'_>> {
+    let mut rest = s;
+    std::iter::from_fn(move || {
+        let mut chars = rest.char_indices();
+        let (_, c) = chars.next()?;
+        let end = if c != '\x1b' {
+            c.len_utf8()
+        } else {
+            match chars.next() {
+                Some((_, '[')) => chars
+                    .find(|(_, c)| ('\x40'..='\x7e').contains(c))
+                    .map_or(rest.len(), |(i, _)| i + 1),
+                Some((_, ']')) => {
+                    let mut prev = ' ';
+                    chars
+                        .find(|&(_, c)| {
+                            let found = c == '\x07' || (prev == '\x1b' && c == '\\');
+                            prev = c;
+                            found
+                        })
+                        .map_or(rest.len(), |(i, _)| i + 1)
+                }
+                Some((i, c)) => i + c.len_utf8(),
+                None => 1,
+            }
+        };
+        let (token, tail) = rest.split_at(end);
+        rest = tail;
+        Some(if c == '\x1b' {
+            Token::Escape(token)
+        } else {
+            Token::Char(c)
+        })
+    })
+}
+
+///Returns the number of columns the string takes up on the terminal. Escape
+///sequences take no space and wide characters take two columns.
+pub fn width(s: &str) -> usize {
+    tokens(s)
+        .map(|t| match t {
+            Token::Char(c) => unicode_width::UnicodeWidthChar::width(c).unwrap_or(0),
+            Token::Escape(_) => 0,
+        })
+        .sum()
+}
+
+///Cuts the string down to at most *width* columns, keeping the escape
+///sequences so that the styles of the cut text stay the same
+pub fn truncate(s: &str, width: usize) -> String {
+    let mut ret = String::new();
+    let mut used = 0;
+    let mut cut = false;
+    for t in tokens(s) {
+        match t {
+            Token::Escape(e) => ret.push_str(e),
+            Token::Char(_) if cut => (),
+            Token::Char(c) => {
+                let w = unicode_width::UnicodeWidthChar::width(c).unwrap_or(0);
+                if used + w <= width {
+                    used += w;
+                    ret.push(c);
+                } else {
+                    cut = true;
+                }
+            }
+        }
+    }
+    ret
+}
+
+///Where a smaller block goes within a larger space
+#[derive(Eq, PartialEq, Clone, Copy, Debug)]
+pub enum Align {
+    ///The left or the top
+    Start,
+    ///The middle, leaning to the start when the space can not be split evenly
+    Center,
+    ///The right or the bottom
+    End,
+}
+
+impl Align {
+    //Splits *extra* cells into the space before and after a block
+    fn split(&self, extra: usize) -> (usize, usize) {
+        match self {
+            Align::Start => (0, extra),
+            Align::Center => (extra / 2, extra - extra / 2),
+            Align::End => (extra, 0),
+        }
+    }
+}
+
+//Pads a line with spaces to *width* columns
+fn pad_line(line: &str, width: usize, align: Align) -> String {
+    let (before, after) = align.split(width.saturating_sub(self::width(line)));
+    format!("{}{}{}", " ".repeat(before), line, " ".repeat(after))
+}
+
+///Puts blocks of text next to each other. Every block is padded to its widest
+///line and shorter blocks are aligned vertically with *align*. Escape
+///sequences are not counted in the width of the lines.
+pub fn join_horizontal(align: Align, blocks: &[&str]) -> String {
+    let blocks: Vec<Vec<&str>> = blocks.iter().map(|b| crate::layout::lines(b)).collect();
+    let height = blocks.iter().map(Vec::len).max().unwrap_or(0);
+    let mut rows = vec![String::new(); height];
+    for block in &blocks {
+        let width = block.iter().map(|l| self::width(l)).max().unwrap_or(0);
+        let (before, _) = align.split(height - block.len());
+        for (i, row) in rows.iter_mut().enumerate() {
+            let line = i
+                .checked_sub(before)
+                .and_then(|i| block.get(i))
+                .unwrap_or(&"");
+            *row += &pad_line(line, width, Align::Start);
+        }
+    }
+    rows.join("\r\n")
+}
+
+///Puts blocks of text under each other, lines shorter than the widest line
+///are aligned horizontally with *align*
+pub fn join_vertical(align: Align, blocks: &[&str]) -> String {
+    let lines: Vec<&str> = blocks
+        .iter()
+        .flat_map(|b| crate::layout::lines(b))
+        .collect();
+    let width = lines.iter().map(|l| self::width(l)).max().unwrap_or(0);
+    lines
+        .iter()
+        .map(|l| pad_line(l, width, align))
+        .collect::<Vec<_>>()
+        .join("\r\n")
+}
+
+///Places *content* in a box of *width* by *height* cells aligned with
+///*h_align* and *v_align*. Content that does not fit is cut.
+pub fn place(width: u16, height: u16, h_align: Align, v_align: Align, content: &str) -> String {
+    let (width, height) = (width as usize, height as usize);
+    let lines = crate::layout::lines(content);
+    let content_width = lines.iter().map(|l| self::width(l)).max().unwrap_or(0);
+    let (left, _) = h_align.split(width.saturating_sub(content_width));
+    let (top, _) = v_align.split(height.saturating_sub(lines.len()));
+    let skip = v_align.split(lines.len().saturating_sub(height)).0;
+    (0..height)
+        .map(|row| {
+            let line = row
+                .checked_sub(top)
+                .and_then(|i| lines.get(i + skip))
+                .unwrap_or(&"");
+            let line = format!("{}{}", " ".repeat(left), line);
+            pad_line(&truncate(&line, width), width, Align::Start)
+        })
+        .collect::<Vec<_>>()
+        .join("\r\n")
+}
+
 #[cfg(test)]
 mod tests {
     // Note this useful idiom: importing names from outer (for mod tests) scope.
@@ -567,6 +732,46 @@ mod tests {
         );
     }
 
+    #[test]
+    fn width_ignores_escapes() {
+        let s = StyleSheet::new()
+            .add(Style::TextColor(Color::Red))
+            .render("hello");
+        assert_eq!(width(&s), 5);
+        assert_eq!(width("\x1b]8;;url\x1b\\日本\x1b]8;;\x07"), 4);
+        assert_eq!(truncate(&s, 3), "\u{1b}[38;5;1mhel\u{1b}[m");
+        assert_eq!(truncate("日本", 3), "日");
+        assert_eq!(truncate("日本a", 3), "日");
+    }
+
+    #[test]
+    fn join() {
+        let red = StyleSheet::new()
+            .add(Style::TextColor(Color::Red))
+            .render("ab");
+        assert_eq!(
+            join_horizontal(Align::End, &[&format!("{}\r\nc", red), "d"]),
+            format!("{} \r\nc d", red)
+        );
+        assert_eq!(
+            join_horizontal(Align::Center, &["a\nb\nc", "d"]),
+            "a \r\nbd\r\nc "
+        );
+        assert_eq!(join_vertical(Align::Center, &["abc", "d"]), "abc\r\n d ");
+        assert_eq!(
+            join_vertical(Align::End, &[&red, "d"]),
+            format!("{}\r\n d", red)
+        );
+    }
+    #[test]
+    fn place_content() {
+        assert_eq!(
+            place(4, 3, Align::Center, Align::End, "ab"),
+            "    \r\n    \r\n ab "
+        );
+        assert_eq!(place(2, 1, Align::Start, Align::Start, "abc\r\nd"), "ab");
+    }
+
     #[test]
     fn position_abs() {
         assert_eq!(
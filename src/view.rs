@@ -0,0 +1,144 @@
+///Where the terminal's cursor should be after a frame has been drawn
+#[derive(Eq, PartialEq, Clone, Debug, Default)]
+pub enum Cursor {
+    ///Leave the cursor at the end of the content
+    #[default]
+    End,
+    ///Hide the cursor
+    Hidden,
+    ///Show the cursor at the given zero indexed position
+    At(u16, u16),
+}
+
+///A frame's content and the metadata that can not be expressed by a plain
+///string. Models that only need the content can keep implementing
+///*Model::view*, models that need more should implement *Model::rich_view*.
+#[derive(Eq, PartialEq, Clone, Debug, Default)]
+pub struct View {
+    ///The text to be displayed
+    pub content: String,
+    ///The position of the cursor
+    pub cursor: Cursor,
+    ///The window's title, the title is left unchanged if this is None
+    pub title: Option<String>,
+    ///Cells to draw instead of the content. Only the cells that changed since
+    ///the last frame are redrawn
+    pub buffer: Option<crate::buffer::Buffer>,
+}
+
+impl View {
+    ///Creates a view that only has content
+    pub fn new<S: Into<String>>(content: S) -> Self {
+        View {
+            content: content.into(),
+            ..Default::default()
+        }
+    }
+    ///Sets the position of the cursor
+    pub fn cursor(mut self, cursor: Cursor) -> Self {
+        self.cursor = cursor;
+        self
+    }
+    ///Sets the window's title
+    pub fn title<S: Into<String>>(mut self, title: S) -> Self {
+        self.title = Some(title.into());
+        self
+    }
+    ///Draws the buffer instead of the content
+    pub fn buffer(mut self, buffer: crate::buffer::Buffer) -> Self {
+        self.buffer = Some(buffer);
+        self
+    }
+}
+
+impl From<String> for View {
+    fn from(content: String) -> Self {
+        View::new(content)
+    }
+}
+
+impl From<&str> for View {
+    fn from(content: &str) -> Self {
+        View::new(content)
+    }
+}
+
+///Turns *text* into a hyperlink to *url* using an OSC 8 sequence. Terminals
+///that do not support hyperlinks display the text as is.
+pub fn hyperlink(url: &str, text: &str) -> String {
+    format!("\x1b]8;;{}\x1b\\{}\x1b]8;;\x1b\\", url, text)
+}
+
+///Returns the escape sequences that draw *view* given the previously drawn
+///view, the title is only written when it changes
+pub(crate) fn draw(view: &View, last: Option<&View>) -> String {
+    let mut ret = String::new();
+    if let Some(title) = &view.title {
+        if last.and_then(|l| l.title.as_ref()) != Some(title) {
+            ret += &format!("\x1b]2;{}\x07", title);
+        }
+    }
+    let last_buffer = last.and_then(|l| l.buffer.as_ref());
+    match &view.buffer {
+        Some(buffer) if last_buffer.map(|l| l.area()) == Some(buffer.area()) => {
+            ret += &buffer.diff(last_buffer)
+        }
+        Some(buffer) => ret += &format!("{}{}", termion::clear::All, buffer.diff(None)),
+        None => {
+            ret += &format!(
+                "{}{}{}",
+                termion::clear::All,
+                termion::cursor::Goto(1, 1),
+                view.content
+            )
+        }
+    }
+    match view.cursor {
+        Cursor::End => ret += &format!("{}", termion::cursor::Show),
+        Cursor::Hidden => ret += &format!("{}", termion::cursor::Hide),
+        Cursor::At(x, y) => {
+            ret += &format!(
+                "{}{}",
+                termion::cursor::Goto(x + 1, y + 1),
+                termion::cursor::Show
+            )
+        }
+    }
+    ret
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn draw_cursor() {
+        let view = View::new("hi").cursor(Cursor::At(1, 0));
+        assert_eq!(draw(&view, None), "\x1b[2J\x1b[1;1Hhi\x1b[1;2H\x1b[?25h");
+    }
+    #[test]
+    fn title_is_only_written_on_change() {
+        let first = View::new("a").title("app");
+        assert!(draw(&first, None).starts_with("\x1b]2;app\x07"));
+        assert!(!draw(&View::new("b").title("app"), Some(&first)).contains("\x1b]2;"));
+    }
+    #[test]
+    fn buffer_is_diffed() {
+        let mut buffer = crate::buffer::Buffer::new(2, 1);
+        buffer.set_string(0, 0, "ab", &[]);
+        let first = View::default()
+            .buffer(buffer.clone())
+            .cursor(Cursor::Hidden);
+        assert_eq!(draw(&first, None), "\x1b[2J\x1b[1;1Hab\x1b[?25l");
+        buffer.set_string(1, 0, "c", &[]);
+        let second = View::default().buffer(buffer).cursor(Cursor::Hidden);
+        assert_eq!(draw(&second, Some(&first)), "\x1b[1;2Hc\x1b[?25l");
+    }
+    #[test]
+    fn link() {
+        assert_eq!(
+            hyperlink("https://example.com", "here"),
+            "\x1b]8;;https://example.com\x1b\\here\x1b]8;;\x1b\\"
+        );
+    }
+}
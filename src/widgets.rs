@@ -1,3 +1,4 @@
+use crate::layout::{BoxConstraints, Rect, Size};
 use std::fmt::Write as _;
 ///The trait that all widgets must implement
 pub trait Widget<E: crate::Event + Send> {
@@ -7,55 +8,116 @@ pub trait Widget<E: crate::Event + Send> {
     fn update(&mut self, e: &E) -> Vec<Box<dyn FnOnce() -> E + Send + 'static>>;
     ///Returns the string representation of a widget
     fn view(&self) -> String;
+    ///Tells the widget whether it has the keyboard focus so that it can
+    ///ignore key presses and render itself differently when it does not.
+    ///Widgets are focused until told otherwise
+    fn set_focused(&mut self, _focused: bool) {}
+    ///Returns the size the widget would like to take within *constraints*,
+    ///by default the size of its view
+    fn measure(&self, constraints: BoxConstraints) -> Size {
+        constraints.constrain(crate::layout::measure(&self.view()))
+    }
+    ///Draws the widget into *area*, by default its view is cut or padded to
+    ///the size of the area
+    fn render(&self, area: Rect) -> String {
+        crate::layout::fit(&self.view(), area)
+    }
+    ///Paints the widget into *area* of *buf*, by default what *render* draws
+    fn paint(&self, buf: &mut crate::buffer::Buffer, area: Rect) {
+        buf.set_styled(area, &self.render(area))
+    }
+}
+
+//Lets containers hand back the concrete type of the widgets they own
+trait AnyWidget<E: crate::Event + Send>: Widget<E> {
+    fn as_any(&self) -> &dyn std::any::Any;
+    fn as_any_mut(&mut self) -> &mut dyn std::any::Any;
+}
+
+impl<E: crate::Event + Send, W: Widget<E> + 'static> AnyWidget<E> for W {
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+    fn as_any_mut(&mut self) -> &mut dyn std::any::Any {
+        self
+    }
+}
+
+///Allocates an id that is unique within the process. Widgets that schedule
+///their own *SystemEvent::Tick*s use it so that two instances never react to
+///each other's ticks.
+pub fn new_id() -> u64 {
+    static NEXT_ID: std::sync::atomic::AtomicU64 = std::sync::atomic::AtomicU64::new(1);
+    NEXT_ID.fetch_add(1, std::sync::atomic::Ordering::Relaxed)
+}
+
+//A command that sends a tick for the given widget after *after*
+fn tick<E: crate::Event + Send + 'static>(
+    id: u64,
+    generation: u64,
+    after: std::time::Duration,
+) -> Box<dyn FnOnce() -> E + Send + 'static> {
+    Box::new(move || {
+        std::thread::sleep(after);
+        E::from_system_event(crate::SystemEvent::Tick(id, generation))
+    })
+}
+
+//Is *e* the current tick of the widget
+fn is_tick<E: crate::Event>(e: &E, id: u64, generation: u64) -> bool {
+    e.to_system_event() == Some(crate::SystemEvent::Tick(id, generation))
 }
 
 ///The structure representation of a spinner
 pub struct Spinner<E: crate::Event + Send + 'static> {
     paused: bool,
-    update_event: E,
     pause_event: E,
     resume_event: E,
+    id: u64,
+    generation: u64,
     idx: usize,
 }
 
 impl<E: crate::Event + Send + 'static> Spinner<E> {
-    //Create a new spinner structure
-    //*update_event* is the event the will be sent and received on updates
-    //*pause_event* is the event the will be received on pause
-    //*resume_event* is the event the will be received on resume
-    pub fn new(update_event: E, pause_event: E, resume_event: E) -> Self {
+    ///Create a new spinner that starts spinning once initialized with
+    ///*pause_event* being the event the will be received on pause
+    ///*resume_event* being the event the will be received on resume
+    pub fn new(pause_event: E, resume_event: E) -> Self {
         Spinner {
-            update_event,
             pause_event,
             resume_event,
             paused: true,
+            id: new_id(),
+            generation: 0,
             idx: 0,
         }
     }
+    ///The id of the ticks of the spinner
+    pub fn id(&self) -> u64 {
+        self.id
+    }
+    fn resume(&mut self) -> Vec<Box<dyn FnOnce() -> E + Send + 'static>> {
+        self.paused = false;
+        self.generation += 1;
+        vec![tick(self.id, self.generation, SPINNER_INTERVAL)]
+    }
 }
 
 const SPINNER_FRAMES: [&str; 8] = ["⣾ ", "⣽ ", "⣻ ", "⢿ ", "⡿ ", "⣟ ", "⣯ ", "⣷ "];
+const SPINNER_INTERVAL: std::time::Duration = std::time::Duration::from_millis(100);
 
 impl<E: crate::Event + Send + 'static> Widget<E> for Spinner<E> {
     fn init(&mut self) -> Vec<Box<dyn FnOnce() -> E + Send + 'static>> {
-        let e = self.resume_event.clone();
-        vec![Box::new(move || e)]
+        self.resume()
     }
     fn update(&mut self, e: &E) -> Vec<Box<dyn FnOnce() -> E + Send + 'static>> {
-        let e = e.clone();
-        let update = self.update_event.clone();
-        let updater = Box::new(|| {
-            std::thread::sleep(std::time::Duration::new(0, 100000000));
-            update
-        });
-        if self.update_event == e && !self.paused {
+        if is_tick(e, self.id, self.generation) && !self.paused {
             self.idx = (self.idx + 1) % SPINNER_FRAMES.len();
-            vec![updater]
-        } else if self.resume_event == e && self.paused {
-            self.paused = false;
-            vec![updater]
+            vec![tick(self.id, self.generation, SPINNER_INTERVAL)]
+        } else if *e == self.resume_event && self.paused {
+            self.resume()
         } else {
-            if self.pause_event == e {
+            if *e == self.pause_event {
                 self.paused = true;
             }
             vec![]
@@ -89,6 +151,26 @@ impl<E: crate::Event + Send + 'static> ProgressBar<E> {
             idx: 0,
         }
     }
+    //Draws the bar scaled to *cells* characters followed by the percentage
+    fn bar(&self, cells: usize) -> String {
+        use crate::style::*;
+        let filled = self.idx * cells / self.n_elements;
+        format!(
+            "{} {}%",
+            (0..cells)
+                .map(|x| {
+                    if x < filled {
+                        StyleSheet::new()
+                            .add(Style::TextColor(self.color.clone()))
+                            .render('█')
+                    } else {
+                        "░".to_string()
+                    }
+                })
+                .fold(String::new(), |x, y| x + &y),
+            self.idx * 100 / self.n_elements,
+        )
+    }
 }
 impl<E: crate::Event + Send + 'static> Widget<E> for ProgressBar<E> {
     fn init(&mut self) -> Vec<Box<dyn FnOnce() -> E + Send + 'static>> {
@@ -104,23 +186,18 @@ impl<E: crate::Event + Send + 'static> Widget<E> for ProgressBar<E> {
         vec![]
     }
     fn view(&self) -> String {
-        use crate::style::*;
-        let bc = &self.color;
-        format!(
-            "{} {}%",
-            (0..self.n_elements)
-                .map(move |x| {
-                    if x < self.idx {
-                        StyleSheet::new()
-                            .add(Style::TextColor(bc.clone()))
-                            .render('█')
-                    } else {
-                        "░".to_string()
-                    }
-                })
-                .fold(String::new(), |x, y| x + &y),
-            self.idx * 100 / self.n_elements,
-        )
+        self.bar(self.n_elements)
+    }
+    fn measure(&self, constraints: BoxConstraints) -> Size {
+        let width = u16::try_from(self.n_elements)
+            .unwrap_or(u16::MAX)
+            .saturating_add(5);
+        constraints.constrain(Size::new(width, 1))
+    }
+    fn render(&self, area: Rect) -> String {
+        let percent = format!(" {}%", self.idx * 100 / self.n_elements);
+        let cells = (area.width as usize).saturating_sub(percent.len());
+        crate::layout::fit(&self.bar(cells), area)
     }
 }
 
@@ -129,67 +206,63 @@ pub struct Timer<E: crate::Event + Send + 'static> {
     completed_event: E,
     pause_event: E,
     resume_event: E,
-    update_event: E,
+    id: u64,
+    generation: u64,
     paused: bool,
     ///The amount of time in seconds left for the timer to finish
     pub time: f32,
 }
+
+const CLOCK_INTERVAL: std::time::Duration = std::time::Duration::from_millis(10);
+
 impl<E: crate::Event + Send + 'static> Timer<E> {
-    ///Create a new timer with
+    ///Create a new timer that starts once initialized with
     ///*completed_event* being the event to emmit when the timer is finished
     ///*pause_event* being the event that the timer receives to pause
     ///*resume_event* being the event that the timer receives to resume
-    ///*update_event* being the event that the timer receives to update
     ///*time* the amount of time in seconds for the timer to finish
-    pub fn new(
-        completed_event: E,
-        pause_event: E,
-        resume_event: E,
-        update_event: E,
-        time: f32,
-    ) -> Self {
+    pub fn new(completed_event: E, pause_event: E, resume_event: E, time: f32) -> Self {
         let time = (time * 100.0).round() / 100.0;
         Timer {
             completed_event,
             pause_event,
             resume_event,
-            update_event,
+            id: new_id(),
+            generation: 0,
             time,
             paused: true,
         }
     }
+    ///The id of the ticks of the timer
+    pub fn id(&self) -> u64 {
+        self.id
+    }
+    fn resume(&mut self) -> Vec<Box<dyn FnOnce() -> E + Send + 'static>> {
+        self.paused = false;
+        self.generation += 1;
+        vec![tick(self.id, self.generation, CLOCK_INTERVAL)]
+    }
 }
 impl<E: crate::Event + Send + 'static> Widget<E> for Timer<E> {
     fn init(&mut self) -> Vec<Box<dyn FnOnce() -> E + Send + 'static>> {
-        let ns = self.resume_event.clone();
-        vec![Box::new(|| {
-            std::thread::sleep(std::time::Duration::new(0, 10000000));
-            ns
-        })]
+        self.resume()
     }
     fn update(&mut self, e: &E) -> Vec<Box<dyn FnOnce() -> E + Send + 'static>> {
-        let e = e.clone();
-        if e == self.update_event && self.time > 0.0 && !self.paused {
+        if is_tick(e, self.id, self.generation) && self.time > 0.0 && !self.paused {
             self.time = ((self.time - 0.01) * 100.0).round() / 100.0;
             vec![if self.time == 0.0 {
                 let e = self.completed_event.clone();
                 Box::new(move || e)
             } else {
-                let e = self.update_event.clone();
-                Box::new(move || {
-                    std::thread::sleep(std::time::Duration::new(0, 10000000));
-                    e
-                })
+                tick(self.id, self.generation, CLOCK_INTERVAL)
             }]
-        } else if e == self.pause_event {
+        } else if *e == self.pause_event {
             self.paused = true;
             vec![]
-        } else if e == self.resume_event && self.paused {
-            self.paused = false;
-            let e = self.update_event.clone();
-            vec![Box::new(move || e)]
+        } else if *e == self.resume_event && self.paused {
+            self.resume()
         } else {
-            vec![Box::new(move || e)]
+            vec![]
         }
     }
     fn view(&self) -> String {
@@ -201,48 +274,51 @@ impl<E: crate::Event + Send + 'static> Widget<E> for Timer<E> {
 pub struct StopWatch<E: crate::Event + Send + 'static> {
     resume_event: E,
     pause_event: E,
-    update_event: E,
+    id: u64,
+    generation: u64,
     paused: bool,
     ///The amount of seconds the stopwatch has been running
     pub time: f32,
 }
 
 impl<E: crate::Event + Send + 'static> StopWatch<E> {
-    ///Creates a new stop watch with
+    ///Creates a new stop watch that starts once initialized with
     ///*pause_event* being the event that pauses the stopwatch
     ///*resume_event* being the event that resumes the stopwatch
-    ///*update_event* being the event that updates the stopwatch
-    pub fn new(pause_event: E, resume_event: E, update_event: E) -> Self {
+    pub fn new(pause_event: E, resume_event: E) -> Self {
         StopWatch {
             pause_event,
             resume_event,
-            update_event,
+            id: new_id(),
+            generation: 0,
             time: 0.0,
             paused: true,
         }
     }
+    ///The id of the ticks of the stop watch
+    pub fn id(&self) -> u64 {
+        self.id
+    }
+    fn resume(&mut self) -> Vec<Box<dyn FnOnce() -> E + Send + 'static>> {
+        self.paused = false;
+        self.generation += 1;
+        vec![tick(self.id, self.generation, CLOCK_INTERVAL)]
+    }
 }
 
 impl<E: crate::Event + Send + 'static> Widget<E> for StopWatch<E> {
     fn init(&mut self) -> Vec<Box<dyn FnOnce() -> E + Send + 'static>> {
-        let ns = self.resume_event.clone();
-        vec![Box::new(|| ns)]
+        self.resume()
     }
     fn update(&mut self, e: &E) -> Vec<Box<dyn FnOnce() -> E + Send + 'static>> {
-        let e = e.clone();
-        if e == self.update_event && !self.paused {
+        if is_tick(e, self.id, self.generation) && !self.paused {
             self.time = ((self.time + 0.01) * 100.0).round() / 100.0;
-            vec![Box::new(|| {
-                std::thread::sleep(std::time::Duration::new(0, 10000000));
-                e
-            })]
-        } else if e == self.pause_event {
+            vec![tick(self.id, self.generation, CLOCK_INTERVAL)]
+        } else if *e == self.pause_event {
             self.paused = true;
             vec![]
-        } else if e == self.resume_event && self.paused {
-            let e = self.update_event.clone();
-            self.paused = false;
-            vec![Box::new(move || e)]
+        } else if *e == self.resume_event && self.paused {
+            self.resume()
         } else {
             vec![]
         }
@@ -253,6 +329,44 @@ impl<E: crate::Event + Send + 'static> Widget<E> for StopWatch<E> {
 }
 
 type Styler = Box<dyn Fn(&str) -> String>;
+
+///Decides whether an event triggers one of the controls of a widget. Anything
+///that can be turned into a matcher can be passed where a widget expects one:
+///an event matches the events equal to it, a *keymap::Binding* matches the
+///key presses of its keys and *Matcher::new* takes any predicate.
+pub struct Matcher<E>(Box<dyn Fn(&E) -> bool>);
+
+impl<E> Matcher<E> {
+    ///Creates a matcher out of a predicate
+    pub fn new<F: Fn(&E) -> bool + 'static>(f: F) -> Self {
+        Matcher(Box::new(f))
+    }
+    ///Does the event trigger the control
+    pub fn matches(&self, e: &E) -> bool {
+        (self.0)(e)
+    }
+    ///A matcher that matches the events matched by either matcher
+    pub fn or(self, other: impl Into<Matcher<E>>) -> Self
+    where
+        E: 'static,
+    {
+        let other = other.into();
+        Matcher::new(move |e| self.matches(e) || other.matches(e))
+    }
+}
+
+impl<E: crate::Event> From<E> for Matcher<E> {
+    fn from(event: E) -> Self {
+        Matcher::new(move |e| *e == event)
+    }
+}
+
+impl<E: crate::Event> From<crate::keymap::Binding> for Matcher<E> {
+    fn from(binding: crate::keymap::Binding) -> Self {
+        Matcher::new(move |e| binding.matches(e))
+    }
+}
+
 ///The structure representation of a List
 pub struct List<E: crate::Event + Send + 'static> {
     elements: Vec<String>,
@@ -261,24 +375,24 @@ pub struct List<E: crate::Event + Send + 'static> {
     page_index: usize,
     selected_style: Option<Styler>,
     unselected_style: Option<Styler>,
-    up_event: E,
-    down_event: E,
-    chose_events: Option<(E, E)>,
+    up: Matcher<E>,
+    down: Matcher<E>,
+    chose_events: Option<(Matcher<E>, E)>,
     height: usize,
 }
 impl<E: crate::Event + Send + 'static> List<E> {
     ///Creates a new list with
     ///*elements* being the elements in the list
-    ///*up_event* being the event for moving the cursor up
-    ///*down_event* being the event for moving the cursor down
-    ///*chose_events* the first event is the event that when recived, indicates
-    ///that the selected element has been chosen, the second is the event to be
-    ///emmited when an element has been chosen.
-    pub fn new(
+    ///*up* matching the events for moving the cursor up
+    ///*down* matching the events for moving the cursor down
+    ///*chose_events* the first matches the events that indicate that the
+    ///selected element has been chosen, the second is the event to be emmited
+    ///when an element has been chosen.
+    pub fn new<C: Into<Matcher<E>>>(
         elements: Vec<String>,
-        up_event: E,
-        down_event: E,
-        chose_events: Option<(E, E)>,
+        up: impl Into<Matcher<E>>,
+        down: impl Into<Matcher<E>>,
+        chose_events: Option<(C, E)>,
         selected_style: Option<Styler>,
         unselected_style: Option<Styler>,
         height: u16,
@@ -295,20 +409,34 @@ impl<E: crate::Event + Send + 'static> List<E> {
             page_index: 0,
             selected_style,
             unselected_style,
-            up_event,
-            down_event,
+            up: up.into(),
+            down: down.into(),
             height,
-            chose_events,
+            chose_events: chose_events.map(|(r, s)| (r.into(), s)),
         }
     }
+    fn element_view(&self, i: usize, selected: bool) -> String {
+        let mut ret = String::new();
+        if selected {
+            if let Some(f) = &self.selected_style {
+                ret += &f(&self.elements[i]);
+            } else {
+                write!(ret, "*>{}", self.elements[i]).unwrap();
+            }
+        } else if let Some(f) = &self.unselected_style {
+            ret += &f(&self.elements[i]);
+        } else {
+            ret += &self.elements[i];
+        }
+        ret
+    }
 }
 impl<E: crate::Event + Send + 'static> Widget<E> for List<E> {
     fn init(&mut self) -> Vec<Box<dyn FnOnce() -> E + Send + 'static>> {
         vec![]
     }
     fn update(&mut self, e: &E) -> Vec<Box<dyn FnOnce() -> E + Send + 'static>> {
-        let e = e.clone();
-        if self.down_event == e {
+        if self.down.matches(e) {
             let new_index_in_page = self.index_in_page + 1;
             if new_index_in_page < self.height {
                 self.index_in_page = new_index_in_page;
@@ -319,7 +447,7 @@ impl<E: crate::Event + Send + 'static> Widget<E> for List<E> {
                     self.index_in_page = 0;
                 }
             }
-        } else if self.up_event == e {
+        } else if self.up.matches(e) {
             if let Some(new_index_in_page) = self.index_in_page.checked_sub(1) {
                 self.index_in_page = new_index_in_page;
             } else if let Some(new_page_index) = self.page_index.checked_sub(1) {
@@ -327,8 +455,7 @@ impl<E: crate::Event + Send + 'static> Widget<E> for List<E> {
                 self.index_in_page = self.height - 1;
             }
         } else if let Some((r, s)) = &self.chose_events {
-            let r = r.clone();
-            if e == r {
+            if r.matches(e) {
                 let s = s.clone();
                 return vec![Box::new(|| s)];
             }
@@ -342,21 +469,33 @@ impl<E: crate::Event + Send + 'static> Widget<E> for List<E> {
             if i != first {
                 ret += "\n\r"
             }
-            if i - first == self.index_in_page {
-                if let Some(f) = &self.selected_style {
-                    ret += &f(&self.elements[i]);
-                } else {
-                    write!(ret, "*>{}", self.elements[i]).unwrap();
-                }
-            } else if let Some(f) = &self.unselected_style {
-                ret += &f(&self.elements[i]);
-            } else {
-                ret += &self.elements[i];
-            }
-            println!("{}", self.elements[i]);
+            ret += &self.element_view(i, i - first == self.index_in_page);
         }
         ret
     }
+    fn measure(&self, constraints: BoxConstraints) -> Size {
+        let width = (0..self.elements.len())
+            .map(|i| {
+                let selected = crate::style::width(&self.element_view(i, true));
+                selected.max(crate::style::width(&self.element_view(i, false)))
+            })
+            .max()
+            .unwrap_or(0);
+        constraints.constrain(Size::new(width as u16, self.elements.len() as u16))
+    }
+    fn render(&self, area: Rect) -> String {
+        if self.elements.is_empty() {
+            return crate::layout::fit("", area);
+        }
+        let height = area.height.max(1) as usize;
+        let selected = self.pages[self.page_index].0 + self.index_in_page;
+        let first = selected / height * height;
+        let end = (first + height).min(self.elements.len());
+        let lines: Vec<String> = (first..end)
+            .map(|i| self.element_view(i, i == selected))
+            .collect();
+        crate::layout::fit(&lines.join("\r\n"), area)
+    }
 }
 
 ///The structure representation of a text-input
@@ -366,6 +505,7 @@ pub struct TextInput<E: crate::Event + Send + 'static> {
     submission_event: E,
     toggle_blink_event: Option<E>,
     blinks: bool,
+    focused: bool,
 }
 impl<E: crate::Event + Send + 'static> TextInput<E> {
     ///Create a new TextInput with
@@ -379,6 +519,7 @@ impl<E: crate::Event + Send + 'static> TextInput<E> {
             submission_event,
             toggle_blink_event,
             blinks: true,
+            focused: true,
         }
     }
     ///Get the input string
@@ -409,17 +550,13 @@ impl<E: crate::Event + Send + 'static> Widget<E> for TextInput<E> {
                 self.blinks = !self.blinks;
             }
         }
-        if let Some(event) = e.to_system_event() {
+        if let Some(event) = e.to_system_event().filter(|_| self.focused) {
             match event {
-                KeyPress(Left) => {
-                    if self.cursor_index > 0 {
-                        self.cursor_index -= 1;
-                    }
+                KeyPress(Left) if self.cursor_index > 0 => {
+                    self.cursor_index -= 1;
                 }
-                KeyPress(Right) => {
-                    if self.cursor_index < self.input.len() {
-                        self.cursor_index += 1;
-                    }
+                KeyPress(Right) if self.cursor_index < self.input.len() => {
+                    self.cursor_index += 1;
                 }
                 KeyPress(Char('\n')) => {
                     let e = self.submission_event.clone();
@@ -434,15 +571,13 @@ impl<E: crate::Event + Send + 'static> Widget<E> for TextInput<E> {
                     );
                     self.cursor_index += 1
                 }
-                KeyPress(Backspace) => {
-                    if self.cursor_index > 0 {
-                        self.input = format!(
-                            "{}{}",
-                            &self.input[..self.cursor_index - 1],
-                            &self.input[self.cursor_index..],
-                        );
-                        self.cursor_index -= 1
-                    }
+                KeyPress(Backspace) if self.cursor_index > 0 => {
+                    self.input = format!(
+                        "{}{}",
+                        &self.input[..self.cursor_index - 1],
+                        &self.input[self.cursor_index..],
+                    );
+                    self.cursor_index -= 1
                 }
                 _ => (),
             };
@@ -451,7 +586,9 @@ impl<E: crate::Event + Send + 'static> Widget<E> for TextInput<E> {
     }
     fn view(&self) -> String {
         use crate::style::*;
-        let cursor = if self.blinks {
+        let cursor = if !self.focused {
+            String::new()
+        } else if self.blinks {
             StyleSheet::new()
                 .add(Style::TextStyle(TextStyle::Blink))
                 .render("|")
@@ -465,12 +602,15 @@ impl<E: crate::Event + Send + 'static> Widget<E> for TextInput<E> {
             &self.input[self.cursor_index..]
         )
     }
+    fn set_focused(&mut self, focused: bool) {
+        self.focused = focused;
+    }
 }
 
 ///The structure representation
 pub struct ViewPort<E: crate::Event + Send + 'static> {
-    up_event: E,
-    down_event: E,
+    up: Matcher<E>,
+    down: Matcher<E>,
     start_line: usize,
     ///The contents of the string
     pub string: String,
@@ -482,14 +622,20 @@ pub struct ViewPort<E: crate::Event + Send + 'static> {
 impl<E: crate::Event + Send + 'static> ViewPort<E> {
     ///Creates a new viewport with
     ///*string* being the contents of the viewport
-    ///*up_event* being the event to scroll up
-    ///*down_event* being the event to scroll down
+    ///*up* matching the events that scroll up
+    ///*down* matching the events that scroll down
     ///*width* being the width of the viewport
     ///*height* is the height of the viewport
-    pub fn new(string: String, up_event: E, down_event: E, width: u16, height: u16) -> Self {
+    pub fn new(
+        string: String,
+        up: impl Into<Matcher<E>>,
+        down: impl Into<Matcher<E>>,
+        width: u16,
+        height: u16,
+    ) -> Self {
         ViewPort {
-            up_event,
-            down_event,
+            up: up.into(),
+            down: down.into(),
             string,
             width,
             height,
@@ -498,7 +644,8 @@ impl<E: crate::Event + Send + 'static> ViewPort<E> {
     }
     //The way this function is implemented and the way this whole widget is implemented
     //is painfully slow.
-    fn split_lines(&self) -> Vec<String> {
+    fn split_lines(&self, width: u16) -> Vec<String> {
+        let width = width.max(1);
         let mut res = Vec::new();
         let mut char_idx: usize = 0;
         let mut line = String::new();
@@ -512,7 +659,7 @@ impl<E: crate::Event + Send + 'static> ViewPort<E> {
                 char_idx = 0;
                 res.push(line);
                 line = String::new();
-            } else if char_idx >= self.width as usize - 1 {
+            } else if char_idx >= width as usize - 1 {
                 char_idx = 0;
                 line += &chr.to_string();
                 res.push(line);
@@ -535,10 +682,9 @@ impl<E: crate::Event + Send + 'static> Widget<E> for ViewPort<E> {
         vec![]
     }
     fn update(&mut self, e: &E) -> Vec<Box<dyn FnOnce() -> E + Send + 'static>> {
-        let e = e.clone();
-        if e == self.up_event {
+        if self.up.matches(e) {
             self.start_line = self.start_line.checked_sub(1).unwrap_or(self.start_line);
-        } else if e == self.down_event {
+        } else if self.down.matches(e) {
             //I should probably check if the new start_line is in the range of the
             //number of lines here but this is hard to do since the actual
             //lining is done by view
@@ -547,9 +693,648 @@ impl<E: crate::Event + Send + 'static> Widget<E> for ViewPort<E> {
         vec![]
     }
     fn view(&self) -> String {
-        let ret = self.split_lines();
+        let ret = self.split_lines(self.width);
         let end = self.height as usize + self.start_line;
         let end = if end > ret.len() { ret.len() } else { end };
         ret[self.start_line..end].join("\r\n")
     }
+    fn measure(&self, constraints: BoxConstraints) -> Size {
+        constraints.constrain(Size::new(self.width, self.height))
+    }
+    fn render(&self, area: Rect) -> String {
+        let ret = self.split_lines(area.width);
+        let start = self.start_line.min(ret.len());
+        let end = (start + area.height as usize).min(ret.len());
+        crate::layout::fit(&ret[start..end].join("\r\n"), area)
+    }
+}
+
+///The structure representation of a help view generated from a keymap
+pub struct Help<E: crate::Event + Send + 'static> {
+    keymap: crate::keymap::KeyMap,
+    short: Option<Vec<String>>,
+    full: Option<Vec<Vec<String>>>,
+    toggle_event: Option<E>,
+    key_style: Option<Styler>,
+    desc_style: Option<Styler>,
+    show_all: bool,
+    ///The width that the help view has to fit in
+    pub width: u16,
+}
+
+const HELP_SEPARATOR: &str = " • ";
+const HELP_ELLIPSIS: &str = "…";
+const HELP_COLUMN_GAP: &str = "    ";
+
+impl<E: crate::Event + Send + 'static> Help<E> {
+    ///Creates a new help view with
+    ///*keymap* being the bindings to describe
+    ///*toggle_event* the event that switches between the short and full views
+    ///*width* being the width that the help view has to fit in, it is updated
+    ///on every window resize
+    pub fn new(keymap: crate::keymap::KeyMap, toggle_event: Option<E>, width: u16) -> Self {
+        Help {
+            keymap,
+            short: None,
+            full: None,
+            toggle_event,
+            key_style: None,
+            desc_style: None,
+            show_all: false,
+            width,
+        }
+    }
+    ///Sets the actions shown in the short view, all actions are shown by default
+    pub fn short(mut self, actions: &[&str]) -> Self {
+        self.short = Some(actions.iter().map(|x| x.to_string()).collect());
+        self
+    }
+    ///Sets the columns of actions shown in the full view, by default all
+    ///actions are shown in a single column
+    pub fn full(mut self, columns: &[&[&str]]) -> Self {
+        self.full = Some(
+            columns
+                .iter()
+                .map(|c| c.iter().map(|x| x.to_string()).collect())
+                .collect(),
+        );
+        self
+    }
+    ///Sets the styles of the keys and descriptions
+    pub fn styles(mut self, key_style: Option<Styler>, desc_style: Option<Styler>) -> Self {
+        self.key_style = key_style;
+        self.desc_style = desc_style;
+        self
+    }
+    ///Replaces the keymap, needed when bindings are changed or disabled
+    pub fn set_keymap(&mut self, keymap: crate::keymap::KeyMap) {
+        self.keymap = keymap;
+    }
+    ///Is the full view being shown
+    pub fn is_showing_all(&self) -> bool {
+        self.show_all
+    }
+    ///Switches between the short and full views
+    pub fn set_show_all(&mut self, show_all: bool) {
+        self.show_all = show_all;
+    }
+    fn style(style: &Option<Styler>, s: &str) -> String {
+        match style {
+            Some(f) => f(s),
+            None => s.to_string(),
+        }
+    }
+    //The enabled bindings of *actions* as (key, description) pairs, all of
+    //the actions are used if *actions* is None
+    fn entries(&self, actions: Option<&Vec<String>>) -> Vec<(&str, &str)> {
+        let bindings: Vec<_> = match actions {
+            Some(actions) => actions.iter().filter_map(|a| self.keymap.get(a)).collect(),
+            None => self.keymap.bindings().map(|(_, b)| b).collect(),
+        };
+        bindings
+            .into_iter()
+            .filter(|b| b.is_enabled() && !b.get_help().is_empty())
+            .map(|b| (b.get_help_key(), b.get_help()))
+            .collect()
+    }
+    ///Renders the single line help, dropping the entries that do not fit
+    pub fn short_view(&self) -> String {
+        use crate::style::width;
+        let max_width = self.width as usize;
+        let mut used = 0;
+        let mut ret = String::new();
+        for (i, (key, desc)) in self.entries(self.short.as_ref()).into_iter().enumerate() {
+            let sep = if i == 0 { "" } else { HELP_SEPARATOR };
+            let len = width(sep) + width(key) + 1 + width(desc);
+            if used + len > max_width {
+                let sep = if i == 0 { "" } else { " " };
+                if used + sep.len() + width(HELP_ELLIPSIS) <= max_width {
+                    write!(
+                        ret,
+                        "{}{}",
+                        sep,
+                        Self::style(&self.desc_style, HELP_ELLIPSIS)
+                    )
+                    .unwrap();
+                }
+                break;
+            }
+            used += len;
+            write!(
+                ret,
+                "{}{} {}",
+                Self::style(&self.desc_style, sep),
+                Self::style(&self.key_style, key),
+                Self::style(&self.desc_style, desc)
+            )
+            .unwrap();
+        }
+        ret
+    }
+    ///Renders the help in columns, dropping the columns that do not fit
+    pub fn full_view(&self) -> String {
+        use crate::style::width;
+        let columns: Vec<Vec<(&str, &str)>> = match &self.full {
+            Some(columns) => columns.iter().map(|c| self.entries(Some(c))).collect(),
+            None => vec![self.entries(None)],
+        };
+        let columns: Vec<_> = columns.into_iter().filter(|c| !c.is_empty()).collect();
+        //The width of the keys and the width of the whole column
+        let widths: Vec<(usize, usize)> = columns
+            .iter()
+            .map(|c| {
+                let key_width = c.iter().map(|(k, _)| width(k)).max().unwrap_or(0);
+                let desc_width = c.iter().map(|(_, d)| width(d)).max().unwrap_or(0);
+                (key_width, key_width + 1 + desc_width)
+            })
+            .collect();
+        let mut n_columns = 0;
+        let mut used = 0;
+        for (i, (_, w)) in widths.iter().enumerate() {
+            let gap = if i == 0 { 0 } else { HELP_COLUMN_GAP.len() };
+            if used + gap + w > self.width as usize {
+                break;
+            }
+            used += gap + w;
+            n_columns += 1;
+        }
+        let height = columns[..n_columns]
+            .iter()
+            .map(|c| c.len())
+            .max()
+            .unwrap_or(0);
+        let mut lines = Vec::new();
+        for row in 0..height {
+            let mut line = String::new();
+            for (i, column) in columns[..n_columns].iter().enumerate() {
+                let (key_width, column_width) = widths[i];
+                if i != 0 {
+                    line += HELP_COLUMN_GAP;
+                }
+                match column.get(row) {
+                    Some((key, desc)) => {
+                        let pad = column_width - key_width - 1 - width(desc);
+                        write!(
+                            line,
+                            "{}{} {}{}",
+                            Self::style(&self.key_style, key),
+                            " ".repeat(key_width - width(key)),
+                            Self::style(&self.desc_style, desc),
+                            " ".repeat(pad)
+                        )
+                        .unwrap();
+                    }
+                    None => line += &" ".repeat(column_width),
+                }
+            }
+            lines.push(line.trim_end().to_string());
+        }
+        lines.join("\r\n")
+    }
+}
+
+impl<E: crate::Event + Send + 'static> Widget<E> for Help<E> {
+    fn init(&mut self) -> Vec<Box<dyn FnOnce() -> E + Send + 'static>> {
+        vec![]
+    }
+    fn update(&mut self, e: &E) -> Vec<Box<dyn FnOnce() -> E + Send + 'static>> {
+        if self.toggle_event.as_ref() == Some(e) {
+            self.show_all = !self.show_all;
+        } else if let Some(crate::SystemEvent::WindowResize(w, _)) = e.to_system_event() {
+            self.width = w;
+        }
+        vec![]
+    }
+    fn view(&self) -> String {
+        if self.show_all {
+            self.full_view()
+        } else {
+            self.short_view()
+        }
+    }
+}
+
+///The structure representation of a group of widgets of which only one, the
+///focused widget, receives key presses. Every other event is passed to all
+///of the widgets so that timers and blinking cursors keep working.
+pub struct FocusGroup<E: crate::Event + Send + 'static> {
+    widgets: Vec<Box<dyn AnyWidget<E>>>,
+    focused: usize,
+    next: crate::keymap::Binding,
+    prev: crate::keymap::Binding,
+}
+
+impl<E: crate::Event + Send + 'static> FocusGroup<E> {
+    ///Creates an empty focus group where tab focuses the next widget and
+    ///shift+tab the previous one
+    pub fn new() -> Self {
+        FocusGroup {
+            widgets: Vec::new(),
+            focused: 0,
+            next: crate::keymap::Binding::new(vec![crate::Key::Char('\t')]).help("next field"),
+            prev: crate::keymap::Binding::new(vec![crate::Key::BackTab]).help("previous field"),
+        }
+    }
+    ///Adds a widget to the end of the group, the first widget is focused
+    pub fn with<W: Widget<E> + 'static>(mut self, widget: W) -> Self {
+        self.push(widget);
+        self
+    }
+    ///Adds a widget to the end of the group
+    pub fn push<W: Widget<E> + 'static>(&mut self, mut widget: W) {
+        widget.set_focused(self.widgets.is_empty());
+        self.widgets.push(Box::new(widget));
+    }
+    ///Sets the bindings that move the focus to the next and previous widgets
+    pub fn bindings(mut self, next: crate::keymap::Binding, prev: crate::keymap::Binding) -> Self {
+        self.next = next;
+        self.prev = prev;
+        self
+    }
+    ///The number of widgets in the group
+    pub fn len(&self) -> usize {
+        self.widgets.len()
+    }
+    ///Is the group empty
+    pub fn is_empty(&self) -> bool {
+        self.widgets.is_empty()
+    }
+    ///The index of the focused widget
+    pub fn focused(&self) -> usize {
+        self.focused
+    }
+    ///Moves the focus to the widget at *idx*
+    pub fn focus(&mut self, idx: usize) {
+        if idx >= self.widgets.len() {
+            return;
+        }
+        if let Some(w) = self.widgets.get_mut(self.focused) {
+            w.set_focused(false);
+        }
+        self.focused = idx;
+        self.widgets[idx].set_focused(true);
+    }
+    ///The widget at *idx* if it is a *W*
+    pub fn get<W: Widget<E> + 'static>(&self, idx: usize) -> Option<&W> {
+        self.widgets.get(idx)?.as_any().downcast_ref()
+    }
+    ///The widget at *idx* if it is a *W*
+    pub fn get_mut<W: Widget<E> + 'static>(&mut self, idx: usize) -> Option<&mut W> {
+        self.widgets.get_mut(idx)?.as_any_mut().downcast_mut()
+    }
+}
+
+impl<E: crate::Event + Send + 'static> Default for FocusGroup<E> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<E: crate::Event + Send + 'static> Widget<E> for FocusGroup<E> {
+    fn init(&mut self) -> Vec<Box<dyn FnOnce() -> E + Send + 'static>> {
+        self.widgets.iter_mut().flat_map(|w| w.init()).collect()
+    }
+    fn update(&mut self, e: &E) -> Vec<Box<dyn FnOnce() -> E + Send + 'static>> {
+        if self.widgets.is_empty() {
+            return vec![];
+        }
+        if self.next.matches(e) {
+            self.focus((self.focused + 1) % self.widgets.len());
+            vec![]
+        } else if self.prev.matches(e) {
+            self.focus((self.focused + self.widgets.len() - 1) % self.widgets.len());
+            vec![]
+        } else if let Some(crate::SystemEvent::KeyPress(_)) = e.to_system_event() {
+            self.widgets[self.focused].update(e)
+        } else {
+            self.widgets.iter_mut().flat_map(|w| w.update(e)).collect()
+        }
+    }
+    fn view(&self) -> String {
+        self.widgets
+            .iter()
+            .map(|w| w.view())
+            .collect::<Vec<_>>()
+            .join("\r\n")
+    }
+}
+
+///How a *Group* puts the views of its children together
+pub enum GroupLayout {
+    ///One child under the other
+    Vertical,
+    ///One child next to the other, shorter children are padded with empty lines
+    Horizontal,
+    ///The views joined with the given string
+    Separator(String),
+}
+
+type EventRoute<E> = Box<dyn Fn(&E) -> Option<String>>;
+
+///The structure representation of a container of widgets identified by
+///name. The group initializes all of its children, passes events to them and
+///collects the commands they return.
+pub struct Group<E: crate::Event + Send + 'static> {
+    children: Vec<(String, Box<dyn AnyWidget<E>>)>,
+    layout: GroupLayout,
+    router: Option<EventRoute<E>>,
+}
+
+impl<E: crate::Event + Send + 'static> Group<E> {
+    ///Creates an empty group with the given layout
+    pub fn new(layout: GroupLayout) -> Self {
+        Group {
+            children: Vec::new(),
+            layout,
+            router: None,
+        }
+    }
+    ///Adds a child called *id* to the end of the group, adding a child with
+    ///the name of an existing one replaces it
+    pub fn with<W: Widget<E> + 'static>(mut self, id: &str, widget: W) -> Self {
+        self.set_child(id, Box::new(widget));
+        self
+    }
+    ///Adds a child called *id* to a group that was already initialized and
+    ///returns the commands of the child's *init*
+    pub fn insert<W: Widget<E> + 'static>(
+        &mut self,
+        id: &str,
+        mut widget: W,
+    ) -> Vec<Box<dyn FnOnce() -> E + Send + 'static>> {
+        let cmds = widget.init();
+        self.set_child(id, Box::new(widget));
+        cmds
+    }
+    fn set_child(&mut self, id: &str, widget: Box<dyn AnyWidget<E>>) {
+        match self.children.iter_mut().find(|(i, _)| i == id) {
+            Some(child) => child.1 = widget,
+            None => self.children.push((id.to_string(), widget)),
+        }
+    }
+    ///Removes the child called *id*
+    pub fn remove(&mut self, id: &str) {
+        self.children.retain(|(i, _)| i != id);
+    }
+    ///Sets a function that picks the child an event is sent to. Events for
+    ///which it returns *None* are sent to every child.
+    pub fn router<F: Fn(&E) -> Option<String> + 'static>(mut self, router: F) -> Self {
+        self.router = Some(Box::new(router));
+        self
+    }
+    ///Sets the layout of the group
+    pub fn set_layout(&mut self, layout: GroupLayout) {
+        self.layout = layout;
+    }
+    ///The names of the children in order
+    pub fn ids(&self) -> impl Iterator<Item = &str> {
+        self.children.iter().map(|(i, _)| i.as_str())
+    }
+    ///The child called *id* if it is a *W*
+    pub fn get<W: Widget<E> + 'static>(&self, id: &str) -> Option<&W> {
+        let (_, w) = self.children.iter().find(|(i, _)| i == id)?;
+        w.as_any().downcast_ref()
+    }
+    ///The child called *id* if it is a *W*
+    pub fn get_mut<W: Widget<E> + 'static>(&mut self, id: &str) -> Option<&mut W> {
+        let (_, w) = self.children.iter_mut().find(|(i, _)| i == id)?;
+        w.as_any_mut().downcast_mut()
+    }
+    ///Sends an event to the child called *id* only
+    pub fn update_child(
+        &mut self,
+        id: &str,
+        e: &E,
+    ) -> Vec<Box<dyn FnOnce() -> E + Send + 'static>> {
+        match self.children.iter_mut().find(|(i, _)| i == id) {
+            Some((_, w)) => w.update(e),
+            None => vec![],
+        }
+    }
+}
+
+impl<E: crate::Event + Send + 'static> Widget<E> for Group<E> {
+    fn init(&mut self) -> Vec<Box<dyn FnOnce() -> E + Send + 'static>> {
+        self.children
+            .iter_mut()
+            .flat_map(|(_, w)| w.init())
+            .collect()
+    }
+    fn update(&mut self, e: &E) -> Vec<Box<dyn FnOnce() -> E + Send + 'static>> {
+        match self.router.as_ref().and_then(|r| r(e)) {
+            Some(id) => self.update_child(&id, e),
+            None => self
+                .children
+                .iter_mut()
+                .flat_map(|(_, w)| w.update(e))
+                .collect(),
+        }
+    }
+    fn view(&self) -> String {
+        let views = self.children.iter().map(|(_, w)| w.view());
+        match &self.layout {
+            GroupLayout::Vertical => views.collect::<Vec<_>>().join("\r\n"),
+            GroupLayout::Separator(sep) => views.collect::<Vec<_>>().join(sep),
+            GroupLayout::Horizontal => {
+                let views: Vec<String> = views.collect();
+                let views: Vec<&str> = views.iter().map(String::as_str).collect();
+                crate::style::join_horizontal(crate::style::Align::Start, &views)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::keymap::{Binding, KeyMap};
+    use crate::{Key, SystemEvent};
+
+    fn keymap() -> KeyMap {
+        KeyMap::new()
+            .bind("up", Binding::new(vec![Key::Up, Key::Char('k')]).help("up"))
+            .bind(
+                "down",
+                Binding::new(vec![Key::Down, Key::Char('j')]).help("down"),
+            )
+            .bind("quit", Binding::new(vec![Key::Char('q')]).help("quit"))
+            .bind("help", Binding::new(vec![Key::Char('?')]).help("more"))
+    }
+
+    fn key(k: Key) -> SystemEvent {
+        SystemEvent::KeyPress(k)
+    }
+
+    #[test]
+    fn focus_group() {
+        let mut group = FocusGroup::new()
+            .with(TextInput::new(SystemEvent::Quit, None))
+            .with(TextInput::new(SystemEvent::Quit, None));
+        group.update(&key(Key::Char('a')));
+        group.update(&key(Key::Char('\t')));
+        group.update(&key(Key::Char('b')));
+        group.update(&key(Key::BackTab));
+        group.update(&key(Key::BackTab));
+        group.update(&key(Key::Char('c')));
+        assert_eq!(group.focused(), 1);
+        let first: &TextInput<SystemEvent> = group.get(0).unwrap();
+        assert_eq!(first.get_string(), "a");
+        let second: &TextInput<SystemEvent> = group.get(1).unwrap();
+        assert_eq!(second.get_string(), "bc");
+        assert!(group.get::<List<SystemEvent>>(0).is_none());
+        assert!(group.view().starts_with("a\r\nbc"));
+    }
+    #[test]
+    fn group() {
+        let mut group = Group::new(GroupLayout::Horizontal)
+            .with(
+                "bar",
+                ProgressBar::new(
+                    key(Key::Char('+')),
+                    key(Key::Char('-')),
+                    crate::style::Color::Red,
+                    3,
+                ),
+            )
+            .with("input", TextInput::new(SystemEvent::Quit, None))
+            .router(|e| match e {
+                SystemEvent::KeyPress(Key::Char('+' | '-')) => Some("bar".to_string()),
+                SystemEvent::KeyPress(_) => Some("input".to_string()),
+                _ => None,
+            });
+        assert!(group.init().is_empty());
+        group.update(&key(Key::Char('+')));
+        group.update(&key(Key::Char('a')));
+        let input: &TextInput<SystemEvent> = group.get("input").unwrap();
+        assert_eq!(input.get_string(), "a");
+        assert!(group.get::<TextInput<SystemEvent>>("bar").is_none());
+        assert_eq!(group.ids().collect::<Vec<_>>(), vec!["bar", "input"]);
+        group.set_layout(GroupLayout::Separator(String::from("|")));
+        assert!(group.view().contains("33%|a"));
+    }
+    #[test]
+    fn stale_ticks_are_ignored() {
+        let mut a = StopWatch::new(key(Key::Char('p')), key(Key::Char('r')));
+        let mut b = StopWatch::new(key(Key::Char('p')), key(Key::Char('r')));
+        let tick_a = a.init().pop().unwrap()();
+        b.init();
+        assert!(tick_a == SystemEvent::Tick(a.id(), 1));
+        assert_eq!(b.update(&tick_a).len(), 0);
+        assert_eq!(a.update(&tick_a).len(), 1);
+        a.update(&key(Key::Char('p')));
+        a.update(&key(Key::Char('r')));
+        assert!(a.update(&tick_a).is_empty());
+        assert_eq!(a.update(&SystemEvent::Tick(a.id(), 2)).len(), 1);
+        assert_eq!((a.time, b.time), (0.02, 0.0));
+    }
+    #[test]
+    fn list_matchers() {
+        let up = Binding::new(vec![Key::Up, Key::Char('k')]);
+        let down = Matcher::new(|e| {
+            matches!(
+                e,
+                SystemEvent::MousePress(crate::MouseButton::WheelDown, ..)
+            )
+        })
+        .or(key(Key::Down));
+        let mut list = List::new(
+            vec!["a".to_string(), "b".to_string(), "c".to_string()],
+            up,
+            down,
+            Some((key(Key::Char('\n')), SystemEvent::Quit)),
+            None,
+            None,
+            3,
+        );
+        list.update(&SystemEvent::MousePress(
+            crate::MouseButton::WheelDown,
+            0,
+            0,
+        ));
+        list.update(&key(Key::Down));
+        list.update(&key(Key::Char('k')));
+        assert_eq!(list.view(), "a\n\r*>b\n\rc");
+        assert_eq!(list.update(&key(Key::Char('\n'))).len(), 1);
+    }
+    #[test]
+    fn render_into_area() {
+        let elements = ["a", "b", "c", "d"].map(String::from).to_vec();
+        let mut list = List::new(
+            elements,
+            key(Key::Up),
+            key(Key::Down),
+            None::<(SystemEvent, SystemEvent)>,
+            None,
+            None,
+            4,
+        );
+        list.update(&key(Key::Down));
+        list.update(&key(Key::Down));
+        let area = Rect::new(0, 0, 3, 2);
+        assert_eq!(
+            list.measure(BoxConstraints::loose(Size::new(10, 2))),
+            Size::new(3, 2)
+        );
+        assert_eq!(list.render(area), crate::layout::fit("*>c\r\nd", area));
+        let mut bar = ProgressBar::new(key(Key::Up), key(Key::Down), crate::style::Color::Red, 4);
+        bar.update(&key(Key::Up));
+        assert!(bar.render(Rect::new(0, 0, 13, 1)).ends_with("░░░░░░ 25%"));
+        let huge = ProgressBar::new(
+            key(Key::Up),
+            key(Key::Down),
+            crate::style::Color::Red,
+            70000,
+        );
+        assert_eq!(
+            huge.measure(BoxConstraints::loose(Size::new(u16::MAX, 1))),
+            Size::new(u16::MAX, 1)
+        );
+        let empty = List::new(
+            vec![],
+            key(Key::Up),
+            key(Key::Down),
+            None::<(SystemEvent, SystemEvent)>,
+            None,
+            None,
+            4,
+        );
+        assert_eq!(empty.render(area), crate::layout::fit("", area));
+        let viewport = ViewPort::new("abcdef".to_string(), key(Key::Up), key(Key::Down), 10, 1);
+        assert_eq!(
+            viewport.render(Rect::new(0, 0, 3, 2)),
+            crate::layout::fit("abc\r\ndef", Rect::new(0, 0, 3, 2))
+        );
+    }
+    #[test]
+    fn help_short() {
+        let help: Help<SystemEvent> = Help::new(keymap(), None, 80).short(&["up", "down", "quit"]);
+        assert_eq!(help.view(), "↑/k up • ↓/j down • q quit");
+    }
+    #[test]
+    fn help_short_truncated() {
+        let help: Help<SystemEvent> = Help::new(keymap(), None, 20);
+        assert_eq!(help.view(), "↑/k up • ↓/j down …");
+        let help: Help<SystemEvent> = Help::new(keymap(), None, 3);
+        assert_eq!(help.view(), "…");
+    }
+    #[test]
+    fn help_wide_descriptions() {
+        let km = KeyMap::new()
+            .bind("a", Binding::new(vec![Key::Char('a')]).help("日本"))
+            .bind("b", Binding::new(vec![Key::Char('b')]).help("x"));
+        let help: Help<SystemEvent> = Help::new(km.clone(), None, 11);
+        assert_eq!(help.short_view(), "a 日本 …");
+        let help: Help<SystemEvent> = Help::new(km, None, 12).full(&[&["a"], &["b"]]);
+        assert_eq!(help.full_view(), "a 日本");
+    }
+    #[test]
+    fn help_full() {
+        let toggle = SystemEvent::KeyPress(Key::Char('?'));
+        let mut help = Help::new(keymap(), Some(toggle.clone()), 80)
+            .full(&[&["up", "down"], &["quit", "help"]]);
+        help.update(&toggle);
+        assert_eq!(help.view(), "↑/k up      q quit\r\n↓/j down    ? more");
+        help.update(&SystemEvent::WindowResize(12, 10));
+        assert_eq!(help.view(), "↑/k up\r\n↓/j down");
+    }
 }